@@ -3,6 +3,27 @@
 mod minecraft;
 mod security;
 
+pub use minecraft::accounts;
+pub use minecraft::args;
+pub use minecraft::assets;
+pub use minecraft::auth;
+pub use minecraft::auth_config;
+pub use minecraft::auth_error;
+pub use minecraft::credentials;
+pub use minecraft::download;
+pub use minecraft::download::CancelToken;
+pub use minecraft::install;
+pub use minecraft::jdk;
+pub use minecraft::natives;
+pub use minecraft::oauth;
+pub use minecraft::profile;
+pub use minecraft::rules;
+pub use minecraft::runtime;
+pub use minecraft::skin_library;
+pub use minecraft::skin_render;
+pub use minecraft::version;
+pub use minecraft::McError;
+
 use tauri::{AppHandle, Manager};
 use tauri::Emitter;
 use tauri::image::Image;
@@ -16,6 +37,10 @@ pub const TRAY_ICON: tauri::image::Image<'static> =
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_opener::init())
+        .manage(minecraft::auth::AuthState::default())
+        .manage(minecraft::auth::PollRegistry::default())
+        .manage(minecraft::launch::LaunchRegistry::default())
         .setup(|app| {
             let salt_path = app
                 .path()
@@ -25,6 +50,10 @@ pub fn run() {
             app.handle().plugin(
                 tauri_plugin_stronghold::Builder::with_argon2(&salt_path).build()
             )?;
+            let credentials = minecraft::credentials::Credentials::for_platform(app.handle())?;
+            app.manage(credentials);
+            let auth_config = minecraft::auth_config::AuthConfig::load(&app.path().app_config_dir()?)?;
+            app.manage(auth_config);
             build_tray(&app.handle())?;
             Ok(())
         })
@@ -35,13 +64,40 @@ pub fn run() {
             // --- auth submodule ---
             minecraft::auth::mc_ms_start_device_code,
             minecraft::auth::mc_ms_poll_and_store,
+            minecraft::auth::mc_ms_cancel_poll,
+            minecraft::auth::mc_ms_login_browser,
+            minecraft::auth::mc_ms_create_profile,
+            minecraft::auth::mc_ms_cancel_profile_setup,
             minecraft::auth::mc_fetch_profile_from_refresh,
             minecraft::auth::auth_is_connected,
             minecraft::auth::auth_logout,
+            minecraft::auth::auth_list_accounts,
+            minecraft::auth::auth_set_active_account,
+            minecraft::auth::auth_remove_account,
+            minecraft::credentials::auth_credential_status,
+            // --- profile submodule ---
+            minecraft::profile::mc_skin_upload,
+            minecraft::profile::mc_skin_set_url,
+            minecraft::profile::mc_skin_reset,
+            minecraft::profile::mc_capes_list,
+            minecraft::profile::mc_cape_equip,
+            minecraft::profile::mc_cape_hide,
+            minecraft::profile::mc_name_change_info,
+            minecraft::profile::mc_name_available,
+            minecraft::profile::mc_name_change,
+            // --- skin library submodule ---
+            minecraft::skin_library::skin_library_list,
+            minecraft::skin_library::skin_library_import_file,
+            minecraft::skin_library::skin_library_import_current,
+            minecraft::skin_library::skin_library_preview,
+            minecraft::skin_library::skin_library_apply,
+            minecraft::skin_library::skin_library_remove,
             // --- launch submodule ---
             minecraft::launch::mc_launch_server,
-            // --- security module ---
-            security::ks_get_device_secret,
+            minecraft::launch::mc_cancel_launch,
+            minecraft::launch::mc_kill_game,
+            // --- jdk submodule ---
+            minecraft::jdk::mc_list_jdks,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,135 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::auth::McProfileLite;
+
+/* ===================== Index des comptes (accounts.json) ===================== */
+
+/// Métadonnées d'un compte ; le refresh token vit dans le coffre sous `refresh.<uuid>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub uuid: String,
+    pub name: String,
+    pub skin_url: Option<String>,
+    /// Secondes Unix de la dernière utilisation (connexion, sélection, lancement).
+    pub last_used: u64,
+    /// Refresh token révoqué : le compte reste listé mais doit se reconnecter.
+    #[serde(default)]
+    pub needs_relogin: bool,
+}
+
+/// Vue front d'un compte.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSummary {
+    pub uuid: String,
+    pub name: String,
+    pub skin_url: Option<String>,
+    pub last_used: u64,
+    pub needs_relogin: bool,
+    pub active: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountIndex {
+    pub active: Option<String>,
+    pub accounts: Vec<Account>,
+}
+
+/// Clé du refresh token d'un compte dans le coffre.
+pub fn refresh_key(uuid: &str) -> String {
+    format!("refresh.{uuid}")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("accounts.json"))
+}
+
+impl AccountIndex {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let data = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| format!("{}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.uuid == uuid)
+    }
+
+    /// Ajoute ou met à jour le compte du profil et le marque comme utilisé.
+    pub fn upsert(&mut self, profile: &McProfileLite) {
+        let account = Account {
+            uuid: profile.id.clone(),
+            name: profile.name.clone(),
+            skin_url: profile.skin_url.clone(),
+            last_used: now_secs(),
+            needs_relogin: false,
+        };
+        match self.accounts.iter_mut().find(|a| a.uuid == profile.id) {
+            Some(existing) => *existing = account,
+            None => self.accounts.push(account),
+        }
+    }
+
+    pub fn touch(&mut self, uuid: &str) {
+        if let Some(account) = self.accounts.iter_mut().find(|a| a.uuid == uuid) {
+            account.last_used = now_secs();
+        }
+    }
+
+    /// Marque le compte comme à reconnecter ; `upsert` lève la marque à la connexion suivante.
+    pub fn mark_relogin(&mut self, uuid: &str) {
+        if let Some(account) = self.accounts.iter_mut().find(|a| a.uuid == uuid) {
+            account.needs_relogin = true;
+        }
+    }
+
+    /// Retire le compte ; s'il était actif, le plus récemment utilisé prend le relais.
+    pub fn remove(&mut self, uuid: &str) {
+        self.accounts.retain(|a| a.uuid != uuid);
+        if self.active.as_deref() == Some(uuid) {
+            self.active = self
+                .accounts
+                .iter()
+                .max_by_key(|a| a.last_used)
+                .map(|a| a.uuid.clone());
+        }
+    }
+
+    /// Comptes du plus récent au plus ancien.
+    pub fn summaries(&self) -> Vec<AccountSummary> {
+        let mut out: Vec<AccountSummary> = self
+            .accounts
+            .iter()
+            .map(|a| AccountSummary {
+                uuid: a.uuid.clone(),
+                name: a.name.clone(),
+                skin_url: a.skin_url.clone(),
+                last_used: a.last_used,
+                needs_relogin: a.needs_relogin,
+                active: self.active.as_deref() == Some(a.uuid.as_str()),
+            })
+            .collect();
+        out.sort_by_key(|a| Reverse(a.last_used));
+        out
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::assets::{game_assets_dir, AssetLayout};
+use super::install::GameDir;
+use super::rules::{library_applies, rules_allow, Features, Platform};
+use super::version::{Argument, VersionJson};
+
+const LAUNCHER_NAME: &str = "kashirlauncher";
+const LAUNCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/* ===================== Contexte de lancement ===================== */
+
+/// Identité du joueur injectée dans `${auth_*}` / `${user_type}`.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub player_name: String,
+    pub uuid: String,
+    pub access_token: String,
+    pub user_type: String,
+    pub xuid: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    pub natives_dir: PathBuf,
+    pub asset_layout: AssetLayout,
+    /// Sans session, les placeholders `${auth_*}` restent tels quels (aperçu).
+    pub session: Option<Session>,
+    pub ram_mb: u32,
+    pub platform: Platform,
+    pub demo: bool,
+    /// Largeur × hauteur de la fenêtre (feature `has_custom_resolution`).
+    pub resolution: Option<(u32, u32)>,
+    /// `hôte[:port]` à rejoindre directement (feature `is_quick_play_multiplayer`).
+    pub quick_play_server: Option<String>,
+}
+
+impl LaunchOptions {
+    pub fn features(&self) -> Features {
+        Features {
+            is_demo_user: self.demo,
+            has_custom_resolution: self.resolution.is_some(),
+            has_quick_plays_support: false,
+            is_quick_play_singleplayer: false,
+            is_quick_play_multiplayer: self.quick_play_server.is_some(),
+            is_quick_play_realms: false,
+        }
+    }
+}
+
+/* ===================== Classpath ===================== */
+
+/// Librairies applicables (artefacts principaux) puis jar client, dans l'ordre du JSON.
+pub fn classpath(dirs: &GameDir, version: &VersionJson, platform: &Platform) -> Vec<PathBuf> {
+    let mut cp: Vec<PathBuf> = version
+        .libraries
+        .iter()
+        .filter(|lib| library_applies(lib, platform))
+        .filter_map(|lib| lib.downloads.as_ref()?.artifact.as_ref())
+        .map(|a| dirs.libraries().join(&a.path))
+        .collect();
+    cp.push(dirs.client_jar(&version.id));
+    cp
+}
+
+pub fn log_config_path(dirs: &GameDir, file_id: &str) -> PathBuf {
+    dirs.assets().join("log_configs").join(file_id)
+}
+
+/* ===================== Substitution des ${placeholders} ===================== */
+
+fn variables(dirs: &GameDir, version: &VersionJson, opts: &LaunchOptions) -> HashMap<&'static str, String> {
+    let separator = opts.platform.classpath_separator();
+    let cp = classpath(dirs, version, &opts.platform)
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(separator);
+
+    let mut vars = HashMap::from([
+        ("version_name", version.id.clone()),
+        ("version_type", version.kind.clone().unwrap_or_else(|| "release".into())),
+        ("game_directory", dirs.root().display().to_string()),
+        ("assets_root", dirs.assets().display().to_string()),
+        (
+            "game_assets",
+            game_assets_dir(dirs, &version.asset_index.id, opts.asset_layout).display().to_string(),
+        ),
+        ("assets_index_name", version.asset_index.id.clone()),
+        ("user_properties", "{}".into()),
+        ("clientid", String::new()),
+        ("natives_directory", opts.natives_dir.display().to_string()),
+        ("library_directory", dirs.libraries().display().to_string()),
+        ("launcher_name", LAUNCHER_NAME.into()),
+        ("launcher_version", LAUNCHER_VERSION.into()),
+        ("classpath", cp),
+        ("classpath_separator", separator.into()),
+    ]);
+
+    if let Some((w, h)) = opts.resolution {
+        vars.insert("resolution_width", w.to_string());
+        vars.insert("resolution_height", h.to_string());
+    }
+    if let Some(server) = &opts.quick_play_server {
+        vars.insert("quickPlayMultiplayer", server.clone());
+    }
+
+    if let Some(s) = &opts.session {
+        vars.insert("auth_player_name", s.player_name.clone());
+        vars.insert("auth_uuid", s.uuid.clone());
+        vars.insert("auth_access_token", s.access_token.clone());
+        vars.insert("auth_session", format!("token:{}:{}", s.access_token, s.uuid));
+        vars.insert("user_type", s.user_type.clone());
+        vars.insert("auth_xuid", s.xuid.clone().unwrap_or_default());
+    }
+    vars
+}
+
+/// Remplace chaque `${clé}` connue ; les clés inconnues sont laissées intactes.
+pub fn substitute(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let key = &after[..end];
+                match vars.get(key) {
+                    Some(v) => out.push_str(v),
+                    None => out.push_str(&rest[start..start + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn expand(
+    args: &[Argument],
+    vars: &HashMap<&str, String>,
+    platform: &Platform,
+    features: &Features,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    for arg in args {
+        match arg {
+            Argument::Plain(s) => out.push(substitute(s, vars)),
+            Argument::Conditional { rules, value } => {
+                if rules_allow(rules, platform, features) {
+                    out.extend(value.values().iter().map(|s| substitute(s, vars)));
+                }
+            }
+        }
+    }
+    out
+}
+
+/* ===================== Construction de l'argv ===================== */
+
+/// Argv complet après l'exécutable java : arguments JVM, classe principale, arguments de jeu.
+pub fn build_argv(dirs: &GameDir, version: &VersionJson, opts: &LaunchOptions) -> Vec<String> {
+    let vars = variables(dirs, version, opts);
+    let features = opts.features();
+    let mut argv = vec![format!("-Xmx{}m", opts.ram_mb), "-XX:+UseG1GC".to_string()];
+
+    match version.arguments.as_ref().filter(|a| !a.jvm.is_empty()) {
+        Some(a) => argv.extend(expand(&a.jvm, &vars, &opts.platform, &features)),
+        None => {
+            // Versions historiques : pas d'arguments JVM dans le JSON.
+            argv.push(substitute("-Djava.library.path=${natives_directory}", &vars));
+            argv.push("-cp".into());
+            argv.push(substitute("${classpath}", &vars));
+        }
+    }
+
+    if let Some(log) = version.logging.as_ref().and_then(|l| l.client.as_ref()) {
+        let path = log_config_path(dirs, &log.file.id).display().to_string();
+        argv.push(log.argument.replace("${path}", &path));
+    }
+
+    argv.push(version.main_class.clone());
+
+    match (&version.arguments, &version.minecraft_arguments) {
+        (Some(a), _) if !a.game.is_empty() => {
+            argv.extend(expand(&a.game, &vars, &opts.platform, &features))
+        }
+        (_, Some(legacy)) => {
+            argv.extend(legacy.split_whitespace().map(|s| substitute(s, &vars)));
+            // Pas de règles en format historique : options ajoutées à la main.
+            if opts.demo {
+                argv.push("--demo".into());
+            }
+            if let Some((w, h)) = opts.resolution {
+                argv.extend(["--width".into(), w.to_string(), "--height".into(), h.to_string()]);
+            }
+        }
+        _ => {}
+    }
+    argv
+}
+
+/// Rendu lisible d'une commande (aperçu UI) ; les arguments avec espaces sont entre guillemets
+/// et le jeton d'accès de la session, s'il y en a une, est masqué.
+pub fn display_command(program: &str, argv: &[String], session: Option<&Session>) -> String {
+    let mask = |a: &str| match session {
+        Some(s) if !s.access_token.is_empty() => a.replace(&s.access_token, "********"),
+        _ => a.to_string(),
+    };
+    std::iter::once(program)
+        .chain(argv.iter().map(String::as_str))
+        .map(mask)
+        .map(|a| if a.contains(' ') { format!("\"{a}\"") } else { a })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
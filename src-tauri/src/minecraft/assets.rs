@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::download::{Downloader, FileTask};
+use super::install::{cached_json, safe_relative, GameDir};
+use super::version::VersionJson;
+use super::McError;
+
+const ASSETS_BASE_URL: &str = "https://resources.download.minecraft.net";
+
+/* ===================== Index d'assets (assets/indexes/<id>.json) ===================== */
+
+#[derive(Debug, Deserialize)]
+pub struct AssetIndex {
+    /// Index `legacy` (1.6 → 1.7.2) : arborescence lisible sous `assets/virtual/<id>/`.
+    #[serde(default, rename = "virtual")]
+    pub is_virtual: bool,
+    /// Index `pre-1.6` : copie dans `<game_dir>/resources/`.
+    #[serde(default)]
+    pub map_to_resources: bool,
+    pub objects: HashMap<String, AssetObject>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetObject {
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Où le jeu s'attend à trouver ses assets (`${game_assets}`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetLayout {
+    /// Versions récentes : uniquement le store `objects/xx/<hash>`.
+    Hashed,
+    Virtual,
+    Resources,
+}
+
+impl AssetIndex {
+    pub fn layout(&self) -> AssetLayout {
+        if self.map_to_resources {
+            AssetLayout::Resources
+        } else if self.is_virtual {
+            AssetLayout::Virtual
+        } else {
+            AssetLayout::Hashed
+        }
+    }
+}
+
+/* ===================== Chemins ===================== */
+
+pub fn object_path(dirs: &GameDir, hash: &str) -> PathBuf {
+    dirs.assets().join("objects").join(&hash[..2]).join(hash)
+}
+
+/// Dossier pointé par `${game_assets}` selon la disposition de l'index.
+pub fn game_assets_dir(dirs: &GameDir, index_id: &str, layout: AssetLayout) -> PathBuf {
+    match layout {
+        AssetLayout::Hashed => dirs.assets(),
+        AssetLayout::Virtual => dirs.assets().join("virtual").join(index_id),
+        AssetLayout::Resources => dirs.root().join("resources"),
+    }
+}
+
+/* ===================== Téléchargement ===================== */
+
+pub async fn fetch_asset_index(
+    dl: &Downloader,
+    dirs: &GameDir,
+    version: &VersionJson,
+) -> Result<AssetIndex, McError> {
+    let idx = &version.asset_index;
+    let path = dirs.assets().join("indexes").join(format!("{}.json", idx.id));
+    let data = cached_json(dl, &idx.url, &path, Some(&idx.sha1)).await?;
+    serde_json::from_slice(&data).map_err(|e| McError::Json(e.to_string()))
+}
+
+/// Objets du store haché, dédupliqués : toutes les versions partagent le même store.
+pub fn object_tasks(dirs: &GameDir, index: &AssetIndex) -> Vec<FileTask> {
+    let mut seen = HashSet::new();
+    index
+        .objects
+        .values()
+        .filter(|obj| seen.insert(obj.hash.as_str()))
+        .map(|obj| FileTask {
+            url: format!("{ASSETS_BASE_URL}/{}/{}", &obj.hash[..2], obj.hash),
+            path: object_path(dirs, &obj.hash),
+            sha1: obj.hash.clone(),
+            size: obj.size,
+        })
+        .collect()
+}
+
+/* ===================== Matérialisation virtual / resources ===================== */
+
+/// Recopie les objets sous leur nom lisible pour les index `virtual` / `map_to_resources`.
+/// Lien physique quand c'est possible, copie sinon ; les fichiers déjà présents à la bonne
+/// taille sont conservés.
+pub fn materialize(dirs: &GameDir, index_id: &str, index: &AssetIndex) -> Result<(), McError> {
+    let layout = index.layout();
+    if layout == AssetLayout::Hashed {
+        return Ok(());
+    }
+    let base = game_assets_dir(dirs, index_id, layout);
+    let io_err = |p: &Path, e: std::io::Error| McError::Io(format!("{}: {e}", p.display()));
+
+    for (name, obj) in &index.objects {
+        let Some(rel) = safe_relative(name) else {
+            continue;
+        };
+        let target = base.join(rel);
+        if std::fs::metadata(&target).is_ok_and(|m| m.len() == obj.size) {
+            continue;
+        }
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| io_err(dir, e))?;
+        }
+        let _ = std::fs::remove_file(&target);
+        let source = object_path(dirs, &obj.hash);
+        if std::fs::hard_link(&source, &target).is_err() {
+            std::fs::copy(&source, &target).map_err(|e| io_err(&target, e))?;
+        }
+    }
+    Ok(())
+}
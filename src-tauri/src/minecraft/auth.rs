@@ -1,35 +1,83 @@
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use base64::Engine;
 use base64::engine::general_purpose;
-use keyring::credential::CredentialApi;
-use keyring::windows::WinCredential;
 use serde_json::json;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+
+use super::args::Session;
+use super::accounts::{index_path, refresh_key, AccountIndex, AccountSummary};
+use super::auth_config::AuthConfig;
+use super::auth_error::AuthError;
+use super::credentials::{Credentials, REFRESH_KEY};
+use super::download::CancelToken;
+use super::oauth::{self, Loopback, Pkce};
+use super::profile::{self, SkinVariant};
 /* ===================== Config Microsoft ===================== */
-const MS_DEVICE_CODE_URL: &str =
-    "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode";
-const MS_TOKEN_URL: &str =
-    "https://login.microsoftonline.com/consumers/oauth2/v2.0/token";
+// Client ID et URLs des services : voir `auth_config.rs` (env / launcher.json / défauts).
 const MARKET: &str = "fr-FR"; // message localisé
-
-// 👉 Remplace par TON client_id (app publique, "Allow public client flows" = Enabled)
-const MS_CLIENT_ID: &str = "e5a244a8-3f50-41fb-b4fb-5b58bf356f5e";
 const MS_SCOPES: &str = "XboxLive.signin offline_access";
 
-const SERVICE: &str = "ks";
-const USER:    &str = "ksrefresh";
-const TARGET:  &str = "ksmain"; // court et stable (= target_name côté WinCred)
+
+/* ===================== Contexte (coffre, comptes, endpoints, HTTP) ===================== */
+
+/// Émetteur d'événements vers le front (`app.emit` dans l'application).
+pub type EventSink = Arc<dyn Fn(&str, serde_json::Value) + Send + Sync>;
+
+/// Dépendances de la chaîne, sans `AppHandle` : les commandes le construisent depuis l'état
+/// Tauri, les tests d'intégration à la main (coffre mémoire, serveur local).
+#[derive(Clone)]
+pub struct AuthContext {
+    pub credentials: Credentials,
+    pub config: AuthConfig,
+    /// Chemin de `accounts.json`.
+    pub accounts_file: PathBuf,
+    pub state: AuthState,
+    pub http: reqwest::Client,
+    pub events: EventSink,
+}
+
+impl AuthContext {
+    pub fn from_app(app: &AppHandle) -> Result<Self, AuthError> {
+        let emitter = app.clone();
+        Ok(Self {
+            credentials: app.state::<Credentials>().inner().clone(),
+            config: app.state::<AuthConfig>().inner().clone(),
+            accounts_file: index_path(app).map_err(AuthError::Storage)?,
+            state: app.state::<AuthState>().inner().clone(),
+            http: reqwest::Client::new(),
+            events: Arc::new(move |event, payload| {
+                let _ = emitter.emit(event, payload);
+            }),
+        })
+    }
+
+    fn emit(&self, event: &str, payload: serde_json::Value) {
+        (self.events)(event, payload);
+    }
+}
 
 /* ===================== Types ===================== */
 
-fn jwt_claims_scopes(token: &str) -> Option<String> {
-    // payload = 2e segment du JWT
+#[derive(Deserialize)]
+struct JwtClaims {
+    scp: Option<String>,
+    xuid: Option<String>,
+}
+
+fn jwt_claims(token: &str) -> Option<JwtClaims> {
+    // payload = 2e segment du JWT (base64url sans padding)
     let seg = token.split('.').nth(1)?;
-    let padded = match seg.len() % 4 { 2 => format!("{seg}=="), 3 => format!("{seg}="), _ => seg.to_string() };
-    let json = general_purpose::URL_SAFE_NO_PAD.decode(padded).ok()?;
-    #[derive(serde::Deserialize)] struct Claims { scp: Option<String> }
-    serde_json::from_slice::<Claims>(&json).ok()?.scp
+    let json = general_purpose::URL_SAFE_NO_PAD.decode(seg.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+fn jwt_claims_scopes(token: &str) -> Option<String> {
+    jwt_claims(token)?.scp
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +95,8 @@ pub struct DeviceCodeStart {
     pub expires_in: u64,
     pub interval: u64,
     pub message: String,
+    /// Identifiant à passer à `mc_ms_poll_and_store` et `mc_ms_cancel_poll`.
+    pub poll_id: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,24 +128,35 @@ struct MsTokenRaw {
 
 /* ===================== Start Device Code ===================== */
 #[tauri::command]
-pub fn mc_ms_start_device_code() -> Result<DeviceCodeStart, String> {
-    let form = [("client_id", MS_CLIENT_ID), ("scope", MS_SCOPES)];
+pub fn mc_ms_start_device_code(
+    config: State<'_, AuthConfig>,
+    polls: State<'_, PollRegistry>,
+) -> Result<DeviceCodeStart, AuthError> {
+    // Enregistré dès maintenant : une annulation avant le début du poll est prise en compte.
+    let poll_id = polls.register();
+    request_device_code(&config, poll_id.clone())
+        .inspect(|start| polls.expire_after(&poll_id, start.expires_in))
+        .inspect_err(|_| polls.remove(&poll_id))
+}
+
+/// Demande un device code (client HTTP bloquant).
+pub fn request_device_code(config: &AuthConfig, poll_id: String) -> Result<DeviceCodeStart, AuthError> {
+    let form = [("client_id", config.client_id.as_str()), ("scope", MS_SCOPES)];
     let client = reqwest::blocking::Client::new();
     let resp = client
-        .post(MS_DEVICE_CODE_URL)
+        .post(config.device_code_url())
         .query(&[("mkt", MARKET)]) // message FR
         .form(&form)
-        .send()
-        .map_err(|e| format!("http error: {e}"))?;
+        .send()?;
     let status = resp.status();
     let body = resp.text().unwrap_or_default();
 
     if !status.is_success() {
-        return Err(format!("ms device code failed: {status} – {body}"));
+        return Err(AuthError::Service(format!("ms device code failed: {status} – {body}")));
     }
 
     let raw: DeviceCodeRaw =
-        serde_json::from_str(&body).map_err(|e| format!("json error: {e} – body: {body}"))?;
+        serde_json::from_str(&body).map_err(|e| AuthError::Service(format!("json error: {e} – body: {body}")))?;
 
     Ok(DeviceCodeStart {
         user_code: raw.user_code,
@@ -104,12 +165,15 @@ pub fn mc_ms_start_device_code() -> Result<DeviceCodeStart, String> {
         expires_in: raw.expires_in,
         interval: raw.interval.max(3),
         message: raw.message,
+        poll_id,
     })
 }
 
 /* ===================== Args (camelCase + snake_case) ===================== */
 #[derive(Debug, Deserialize)]
 pub struct DevicePollArgs {
+    #[serde(alias = "pollId")]
+    pub poll_id: String,
     #[serde(alias = "deviceCode")]
     pub device_code: String,
     #[serde(alias = "intervalSecs")]
@@ -118,353 +182,870 @@ pub struct DevicePollArgs {
     pub timeout_secs: u64,
 }
 
-/* ===================== Keyring helpers (refresh token only) ===================== */
-#[cfg(windows)]
-fn mask_len(s: &str) -> String {
-    // n’affiche jamais le secret : montre longueur + un mini aperçu masqué
-    let n = s.len();
-    let head = s.chars().take(4).collect::<String>();
-    let tail = s.chars().rev().take(4).collect::<String>().chars().rev().collect::<String>();
-    format!("{}…{} (len={})", head, tail, n)
-}
-
-#[cfg(windows)]
-fn save_refresh_token(_app: &tauri::AppHandle, refresh: &str) -> Result<(), String> {
-    println!("[wincred] save_refresh_token: target={TARGET} service={SERVICE} user={USER} secret={}", mask_len(refresh));
-    let cred = WinCredential::new_with_target(Some(TARGET), SERVICE, USER)
-        .map_err(|e| {
-            println!("[wincred] new_with_target ERR: {:?} / {}", e, e);
-            e.to_string()
-        })?;
-
-    match cred.set_password(refresh) {
-        Ok(()) => {
-            println!("[wincred] set_password OK");
-            // lecture immédiate pour vérifier la persistance
-            match cred.get_password() {
-                Ok(back) => {
-                    println!("[wincred] post-write get_password OK: {}", mask_len(&back));
-                }
-                Err(e) => {
-                    println!("[wincred] post-write get_password ERR: {:?} / {}", e, e);
-                }
-            }
-            Ok(())
-        }
-        Err(e) => {
-            println!("[wincred] set_password ERR: {:?} / {}", e, e);
-            Err(e.to_string())
-        }
-    }
+/* ===================== Coffre (refresh tokens only) ===================== */
+// Chaque compte a son refresh token sous `refresh.<uuid>` ; `key` désigne cette entrée.
+
+/// Refresh token d'une connexion terminée dont le compte n'a pas encore de profil Java :
+/// il y reste jusqu'à `mc_ms_create_profile`, `mc_ms_cancel_profile_setup` ou la connexion suivante.
+const PENDING_KEY: &str = "refresh.pending";
+
+/// Au-delà, une connexion sans profil n'est plus utilisée pour créer le profil.
+const PROFILE_SETUP_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Refresh token d'une connexion en cours dont le compte n'est pas encore identifié ; une
+/// entrée par connexion pour que device code et navigateur puissent se chevaucher.
+fn login_key(login_id: &str) -> String {
+    format!("refresh.login.{login_id}")
 }
 
-#[cfg(windows)]
-fn get_refresh_token(_app: &tauri::AppHandle) -> Result<String, String> {
-    println!("[wincred] get_refresh_token: target={TARGET} service={SERVICE} user={USER}");
-    let cred = WinCredential::new_with_target(Some(TARGET), SERVICE, USER)
-        .map_err(|e| {
-            println!("[wincred] new_with_target ERR: {:?} / {}", e, e);
-            e.to_string()
-        })?;
+/// Identifiant d'une connexion (`poll_id` des device codes).
+fn new_login_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
 
-    match cred.get_password() {
-        Ok(s) => {
-            println!("[wincred] get_password OK: {}", mask_len(&s));
-            Ok(s)
-        }
-        Err(e) => {
-            println!("[wincred] get_password ERR: {:?} / {}", e, e);
-            Err(e.to_string())
-        }
+fn save_refresh_token(ctx: &AuthContext, key: &str, refresh: &str) -> Result<(), AuthError> {
+    ctx.credentials.set(key, refresh).map_err(AuthError::Storage)
+}
+
+fn get_refresh_token(ctx: &AuthContext, key: &str) -> Result<String, AuthError> {
+    ctx.credentials
+        .get(key)
+        .map_err(AuthError::Storage)?
+        .ok_or(AuthError::NotSignedIn)
+}
+
+fn delete_refresh_token(ctx: &AuthContext, key: &str) -> Result<(), AuthError> {
+    ctx.credentials.delete(key).map_err(AuthError::Storage)
+}
+
+fn load_index(ctx: &AuthContext) -> Result<AccountIndex, AuthError> {
+    AccountIndex::load(&ctx.accounts_file).map_err(AuthError::Storage)
+}
+
+fn save_index(ctx: &AuthContext, index: &AccountIndex) -> Result<(), AuthError> {
+    index.save(&ctx.accounts_file).map_err(AuthError::Storage)
+}
+
+/// Entrée du coffre du compte actif ; une installation mono-compte garde l'ancienne clé
+/// jusqu'à sa première identification.
+fn active_key(ctx: &AuthContext) -> Result<String, AuthError> {
+    if let Some(uuid) = load_index(ctx)?.active {
+        return Ok(refresh_key(&uuid));
+    }
+    if ctx.credentials.get(REFRESH_KEY).map_err(AuthError::Storage)?.is_some() {
+        return Ok(REFRESH_KEY.to_string());
     }
+    Err(AuthError::NotSignedIn)
 }
+/* ===================== Poll + Store (async, pas de token vers le front) ===================== */
 
-#[cfg(windows)]
-fn delete_refresh_token(_app: &tauri::AppHandle) -> Result<(), String> {
-    println!("[wincred] delete_refresh_token: target={TARGET} service={SERVICE} user={USER}");
-    let cred = WinCredential::new_with_target(Some(TARGET), SERVICE, USER)
-        .map_err(|e| {
-            println!("[wincred] new_with_target ERR: {:?} / {}", e, e);
-            e.to_string()
-        })?;
+/// Durée de vie d'une entrée tant que le device code n'a pas été obtenu.
+const POLL_REGISTRATION_TTL: Duration = Duration::from_secs(60);
 
-    match cred.delete_credential() {
-        Ok(()) => {
-            println!("[wincred] delete_credential OK");
-            Ok(())
-        }
-        Err(keyring::Error::NoEntry) => {
-            println!("[wincred] delete_credential: NoEntry (déjà supprimé)");
-            Ok(())
-        }
-        Err(e) => {
-            println!("[wincred] delete_credential ERR: {:?} / {}", e, e);
-            Err(e.to_string())
+struct PendingPoll {
+    cancel: CancelToken,
+    /// Fin de validité du device code : au-delà, l'entrée est purgée même sans poll.
+    expires_at: Instant,
+}
+
+/// Polls device code en cours, indexés par le `poll_id` renvoyé par `mc_ms_start_device_code`.
+/// Une entrée disparaît à la fin du poll, à l'annulation ou à l'expiration du device code.
+#[derive(Default)]
+pub struct PollRegistry {
+    polls: Mutex<HashMap<String, PendingPoll>>,
+}
+
+impl PollRegistry {
+    fn register(&self) -> String {
+        let id = new_login_id();
+        let now = Instant::now();
+        let mut polls = self.polls.lock().unwrap();
+        polls.retain(|_, poll| {
+            let alive = poll.expires_at > now;
+            if !alive {
+                poll.cancel.cancel();
+            }
+            alive
+        });
+        let poll = PendingPoll { cancel: CancelToken::default(), expires_at: now + POLL_REGISTRATION_TTL };
+        polls.insert(id.clone(), poll);
+        id
+    }
+
+    fn expire_after(&self, id: &str, expires_in: u64) {
+        if let Some(poll) = self.polls.lock().unwrap().get_mut(id) {
+            poll.expires_at = Instant::now() + Duration::from_secs(expires_in);
         }
     }
+
+    fn token(&self, id: &str) -> Option<CancelToken> {
+        let polls = self.polls.lock().unwrap();
+        polls
+            .get(id)
+            .filter(|poll| poll.expires_at > Instant::now())
+            .map(|poll| poll.cancel.clone())
+    }
+
+    fn remove(&self, id: &str) {
+        self.polls.lock().unwrap().remove(id);
+    }
+
+    fn cancel(&self, id: &str) -> Result<(), String> {
+        let poll = self
+            .polls
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| format!("aucune connexion en cours avec l'id {id}"))?;
+        poll.cancel.cancel();
+        Ok(())
+    }
 }
-/* ===================== Poll + Store (async, pas de token vers le front) ===================== */
+
+/// `auth://status` : `pending`, `slow_down` (avec le nouvel intervalle), `expired`, `denied`,
+/// `cancelled`, puis `success` une fois l'autorisation accordée (la liaison du compte suit).
+fn emit_poll_status(ctx: &AuthContext, poll_id: &str, status: &str, interval: Option<u64>) {
+    ctx.emit("auth://status", json!({ "poll_id": poll_id, "status": status, "interval": interval }));
+}
+
+#[tauri::command]
+/// Ajoute un compte : attend l'autorisation, identifie le profil puis l'active.
+pub async fn mc_ms_poll_and_store(
+    app: AppHandle,
+    polls: State<'_, PollRegistry>,
+    args: DevicePollArgs,
+) -> Result<AccountSummary, AuthError> {
+    // Id inconnu : connexion annulée avant le poll, ou device code expiré.
+    let cancel = polls.token(&args.poll_id).ok_or(AuthError::Cancelled)?;
+    let res = match AuthContext::from_app(&app) {
+        Ok(ctx) => poll_and_store(&ctx, &cancel, &args).await,
+        Err(e) => Err(e),
+    };
+    polls.remove(&args.poll_id);
+    res
+}
+
+/// Cœur de `mc_ms_poll_and_store`.
+pub async fn poll_and_store(ctx: &AuthContext, cancel: &CancelToken, args: &DevicePollArgs) -> Result<AccountSummary, AuthError> {
+    let raw = poll_device_code(ctx, cancel, args).await?;
+    store_new_login(ctx, &args.poll_id, raw.access_token, raw.expires_in, raw.refresh_token).await
+}
+
+/// Interrompt l'attente d'un device code ; `mc_ms_poll_and_store` renvoie alors une erreur.
 #[tauri::command]
-pub async fn mc_ms_poll_and_store(app: AppHandle, args: DevicePollArgs) -> Result<(), String> {
-    let DevicePollArgs {
-        device_code,
-        interval_secs,
-        timeout_secs,
-    } = args;
-
-    let client = reqwest::Client::new();
+pub fn mc_ms_cancel_poll(polls: State<'_, PollRegistry>, poll_id: String) -> Result<(), String> {
+    polls.cancel(&poll_id)
+}
+
+async fn poll_device_code(ctx: &AuthContext, cancel: &CancelToken, args: &DevicePollArgs) -> Result<MsTokenRaw, AuthError> {
+    let poll_id = args.poll_id.as_str();
+    let cancelled = || {
+        emit_poll_status(ctx, poll_id, "cancelled", None);
+        AuthError::Cancelled
+    };
+
+    let token_url = ctx.config.token_url();
     let start = Instant::now();
+    let mut interval_secs = args.interval_secs;
 
     loop {
-        if start.elapsed().as_secs() > timeout_secs {
-            return Err("timeout waiting for authorization".into());
+        if cancel.is_cancelled() {
+            return Err(cancelled());
+        }
+        if start.elapsed().as_secs() > args.timeout_secs {
+            emit_poll_status(ctx, poll_id, "expired", None);
+            return Err(AuthError::Timeout);
         }
 
         let form = [
             ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
-            ("client_id", MS_CLIENT_ID),
-            ("device_code", device_code.as_str()),
+            ("client_id", ctx.config.client_id.as_str()),
+            ("device_code", args.device_code.as_str()),
         ];
 
-        let res = client
-            .post(MS_TOKEN_URL)
-            .form(&form)
-            .send()
-            .await
-            .map_err(|e| format!("http error: {e}"))?;
+        let res = tokio::select! {
+            res = ctx.http.post(&token_url).form(&form).send() => res?,
+            _ = cancel.cancelled() => return Err(cancelled()),
+        };
 
         let status = res.status();
         let text = res.text().await.unwrap_or_default();
 
         if status.is_success() {
             let raw: MsTokenRaw =
-                serde_json::from_str(&text).map_err(|e| format!("json error: {e} – body: {text}"))?;
+                serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
+            emit_poll_status(ctx, poll_id, "success", None);
+            return Ok(raw);
+        }
+
+        let error = serde_json::from_str::<MsErrorResp>(&text).map(|e| e.error).unwrap_or_default();
+        match error.as_str() {
+            "authorization_pending" => emit_poll_status(ctx, poll_id, "pending", None),
+            "slow_down" => {
+                // RFC 8628 §3.5 : +5 s pour toutes les requêtes suivantes
+                interval_secs += ctx.config.slow_down_secs;
+                emit_poll_status(ctx, poll_id, "slow_down", Some(interval_secs));
+            }
+            "expired_token" => {
+                emit_poll_status(ctx, poll_id, "expired", None);
+                return Err(AuthError::DeviceCodeExpired);
+            }
+            "authorization_declined" | "access_denied" => {
+                emit_poll_status(ctx, poll_id, "denied", None);
+                return Err(AuthError::Declined);
+            }
+            _ => return Err(AuthError::Service(format!("token exchange failed: {status} – {text}"))),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval_secs)) => {}
+            _ = cancel.cancelled() => return Err(cancelled()),
+        }
+    }
+}
+
+/// Fin commune des connexions (device code, navigateur) : stocke uniquement le refresh token
+/// (remember me), puis enchaîne XBL→XSTS→Minecraft en mémoire pour savoir à quel compte il appartient.
+async fn store_new_login(
+    ctx: &AuthContext,
+    login_id: &str,
+    access_token: String,
+    expires_in: u64,
+    refresh_token: Option<String>,
+) -> Result<AccountSummary, AuthError> {
+    let Some(refresh) = refresh_token else {
+        return Err(AuthError::Service("no refresh_token returned (scope offline_access manquant ?)".into()));
+    };
+    let key = login_key(login_id);
+    save_refresh_token(ctx, &key, &refresh)?;
+    let seed = TokenCache {
+        ms_access: Some(Cached::for_secs(access_token, expires_in)),
+        ..TokenCache::default()
+    };
+    add_account(ctx, &key, seed).await
+}
+
+/* ===================== Authorization code + PKCE (navigateur système) ===================== */
+const BROWSER_LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
 
-            let Some(refresh) = raw.refresh_token else {
-                return Err("no refresh_token returned (scope offline_access manquant ?)".into());
+#[tauri::command]
+/// Ajoute un compte via le navigateur système : la page Microsoft redirige vers un écouteur
+/// `127.0.0.1` ouvert pour cette seule connexion.
+pub async fn mc_ms_login_browser(app: AppHandle) -> Result<AccountSummary, AuthError> {
+    let pkce = Pkce::generate()?;
+    let state = oauth::random_state();
+    let loopback = Loopback::bind().await?;
+    let redirect_uri = loopback.redirect_uri.clone();
+    let ctx = AuthContext::from_app(&app)?;
+    let cfg = &ctx.config;
+    let url = oauth::authorize_url(&cfg.authorize_url(), &cfg.client_id, MS_SCOPES, &redirect_uri, &pkce, &state)?;
+
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|e| AuthError::Service(format!("ouverture du navigateur impossible: {e}")))?;
+
+    let code = tokio::time::timeout(BROWSER_LOGIN_TIMEOUT, loopback.wait_for_code(&state))
+        .await
+        .map_err(|_| AuthError::Timeout)??;
+
+    let raw = oauth::exchange_code(&ctx.http, &cfg.token_url(), &cfg.client_id, MS_SCOPES, &code, &redirect_uri, &pkce).await?;
+    store_new_login(&ctx, &new_login_id(), raw.access_token, raw.expires_in, raw.refresh_token).await
+}
+
+/* ===================== Cache des jetons (jamais renvoyé au front) ===================== */
+
+/// Marge avant expiration en deçà de laquelle un jeton est considéré périmé.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+struct Cached<T> {
+    value: T,
+    expires_at: SystemTime,
+}
+
+impl<T: Clone> Cached<T> {
+    fn for_secs(value: T, expires_in: u64) -> Self {
+        Self { value, expires_at: SystemTime::now() + Duration::from_secs(expires_in) }
+    }
+
+    fn fresh(&self) -> Option<T> {
+        (SystemTime::now() + EXPIRY_MARGIN < self.expires_at).then(|| self.value.clone())
+    }
+}
+
+#[derive(Clone)]
+struct McIdentity {
+    profile: McProfileLite,
+    xuid: Option<String>,
+}
+
+/// Jeton de chaque étape de la chaîne, avec son expiration (`expires_in` / `NotAfter`).
+#[derive(Default)]
+struct TokenCache {
+    ms_access: Option<Cached<String>>,
+    xbl: Option<Cached<String>>,
+    /// (jeton XSTS, uhs)
+    xsts: Option<Cached<(String, String)>>,
+    mc: Option<Cached<McLoginResp>>,
+    /// Licence vérifiée et profil lu avec le jeton `mc` courant.
+    identity: Option<McIdentity>,
+}
+
+/// Caches de session par entrée du coffre, gérés par Tauri (partagés entre les clones). Le
+/// verrou couvre toute la chaîne : deux appels simultanés ne rejouent pas deux fois les mêmes étapes.
+#[derive(Default, Clone)]
+pub struct AuthState {
+    caches: Arc<tokio::sync::Mutex<HashMap<String, TokenCache>>>,
+    /// Début de la création de profil en cours ; une entrée `PENDING_KEY` sans cette marque
+    /// (redémarrage, délai dépassé) est périmée.
+    profile_setup_since: Arc<Mutex<Option<Instant>>>,
+}
+
+impl AuthState {
+    fn set_profile_setup(&self, since: Option<Instant>) {
+        *self.profile_setup_since.lock().unwrap() = since;
+    }
+
+    fn profile_setup_live(&self) -> bool {
+        self.profile_setup_since
+            .lock()
+            .unwrap()
+            .is_some_and(|since| since.elapsed() < PROFILE_SETUP_TTL)
+    }
+}
+
+/// Rejoue uniquement les étapes expirées et renvoie le jeton Minecraft.
+async fn minecraft_token(ctx: &AuthContext, cache: &mut TokenCache, key: &str) -> Result<String, AuthError> {
+    if let Some(mc) = cache.mc.as_ref().and_then(Cached::fresh) {
+        return Ok(mc.access_token);
+    }
+
+    let xsts = match cache.xsts.as_ref().and_then(Cached::fresh) {
+        Some(xsts) => xsts,
+        None => {
+            let xbl_token = match cache.xbl.as_ref().and_then(Cached::fresh) {
+                Some(token) => token,
+                None => {
+                    let ms_access = match cache.ms_access.as_ref().and_then(Cached::fresh) {
+                        Some(token) => token,
+                        None => {
+                            let refresh = get_refresh_token(ctx, key)
+                                .inspect_err(|_| println!("[auth] chain: no refresh"))?;
+                            println!("[auth] chain: refresh present (len={})", refresh.len());
+                            let ms = match ms_refresh_access_token(ctx, key, &refresh).await {
+                                Ok(ms) => ms,
+                                Err(e) => {
+                                    *cache = TokenCache::default();
+                                    return Err(e);
+                                }
+                            };
+                            println!("[auth] chain: got ms_access (len={})", ms.access_token.len());
+                            let token = ms.access_token.clone();
+                            cache.ms_access = Some(Cached::for_secs(ms.access_token, ms.expires_in));
+                            token
+                        }
+                    };
+                    let xbl = xbl_auth(ctx, &ms_access).await?;
+                    println!("[auth] chain: got xbl_token (len={})", xbl.token.len());
+                    cache.xbl = Some(xbl.cached(xbl.token.clone()));
+                    xbl.token
+                }
             };
+            let xsts = xsts_auth(ctx, &xbl_token).await?;
+            println!("[auth] chain: got xsts (len={}), uhs={}", xsts.token.len(), xsts.uhs);
+            let value = (xsts.token.clone(), xsts.uhs.clone());
+            cache.xsts = Some(xsts.cached(value.clone()));
+            value
+        }
+    };
 
-            // 🔐 Stocke uniquement le refresh token (remember me)
-            save_refresh_token(&app, &refresh)?;
-
-            // Ici tu peux enchaîner XBL→XSTS→Minecraft côté Rust, en mémoire,
-            // et ne jamais exposer les tokens courts au front.
-            return Ok(());
-        } else {
-            if text.contains("authorization_pending") {
-                // continue; on dormira ci-dessous
-            } else if text.contains("slow_down") {
-                tokio::time::sleep(std::time::Duration::from_secs(interval_secs + 2)).await;
-            } else if text.contains("expired_token") {
-                return Err("device code expired".into());
-            } else {
-                return Err(format!("token exchange failed: {status} – {text}"));
-            }
+    let (xsts_token, uhs) = xsts;
+    let mc = mc_login_with_xbox(ctx, &uhs, &xsts_token).await?;
+    println!("[auth] chain: mc login ok, access len={}", mc.access_token.len());
+    let token = mc.access_token.clone();
+    cache.mc = Some(Cached::for_secs(mc.clone(), mc.expires_in));
+    cache.identity = None;
+    Ok(token)
+}
+
+/// Licence + profil pour le jeton Minecraft courant ; `refresh_profile` force la relecture du profil.
+async fn identity(
+    ctx: &AuthContext,
+    cache: &mut TokenCache,
+    key: &str,
+    refresh_profile: bool,
+) -> Result<(String, McIdentity), AuthError> {
+    let token = minecraft_token(ctx, cache, key).await?;
+    if let Some(identity) = cache.identity.clone().filter(|_| !refresh_profile) {
+        return Ok((token, identity));
+    }
+    if cache.identity.is_none() {
+        mc_check_entitlement(ctx, &token).await?;
+        println!("[auth] chain: entitlement ok");
+    }
+    let profile = mc_fetch_profile(ctx, &token).await?;
+    println!("[auth] chain: profile ok: {} ({})", profile.name, profile.id);
+    let identity = McIdentity { profile, xuid: jwt_claims(&token).and_then(|c| c.xuid) };
+    cache.identity = Some(identity.clone());
+    Ok((token, identity))
+}
+
+/// Range le refresh token de `key` sous le compte identifié, l'enregistre et l'active.
+fn register_account(
+    ctx: &AuthContext,
+    caches: &mut HashMap<String, TokenCache>,
+    key: &str,
+    profile: &McProfileLite,
+) -> Result<AccountSummary, AuthError> {
+    let account_key = refresh_key(&profile.id);
+    if key != account_key {
+        move_refresh_token(ctx, caches, key, &account_key)?;
+    }
+    let mut index = load_index(ctx)?;
+    index.upsert(profile);
+    index.active = Some(profile.id.clone());
+    save_index(ctx, &index)?;
+    index
+        .summaries()
+        .into_iter()
+        .find(|a| a.uuid == profile.id)
+        .ok_or_else(|| AuthError::UnknownAccount(profile.id.clone()))
+}
+
+/// Déplace un refresh token (et ses jetons en cache) d'une entrée du coffre à une autre.
+fn move_refresh_token(
+    ctx: &AuthContext,
+    caches: &mut HashMap<String, TokenCache>,
+    from: &str,
+    to: &str,
+) -> Result<(), AuthError> {
+    let refresh = get_refresh_token(ctx, from)?;
+    save_refresh_token(ctx, to, &refresh)?;
+    delete_refresh_token(ctx, from)?;
+    if let Some(cache) = caches.remove(from) {
+        caches.insert(to.to_string(), cache);
+    }
+    Ok(())
+}
+
+/// Oublie la connexion sans profil en attente : refresh token et jetons en cache.
+fn discard_profile_setup(ctx: &AuthContext, caches: &mut HashMap<String, TokenCache>) {
+    ctx.state.set_profile_setup(None);
+    caches.remove(PENDING_KEY);
+    let _ = delete_refresh_token(ctx, PENDING_KEY);
+}
+
+/// Termine une connexion : identifie le compte derrière `key` (voir `login_key`) et l'active.
+/// Une connexion sans profil laissée par une tentative précédente est abandonnée.
+async fn add_account(ctx: &AuthContext, key: &str, seed: TokenCache) -> Result<AccountSummary, AuthError> {
+    let mut caches = ctx.state.caches.lock().await;
+    discard_profile_setup(ctx, &mut caches);
+    let cache = caches.entry(key.to_string()).or_default();
+    *cache = seed;
+    let registered = match identity(ctx, cache, key, true).await {
+        Ok((_, identity)) => register_account(ctx, &mut caches, key, &identity.profile),
+        Err(e) => Err(e),
+    };
+    match &registered {
+        // Jeu acheté sans profil Java : la connexion reste en attente de `mc_ms_create_profile`.
+        Err(AuthError::NoProfile) => {
+            move_refresh_token(ctx, &mut caches, key, PENDING_KEY)?;
+            ctx.state.set_profile_setup(Some(Instant::now()));
         }
+        Err(_) => {
+            caches.remove(key);
+            let _ = delete_refresh_token(ctx, key);
+        }
+        Ok(_) => {}
+    }
+    registered
+}
+
+/// Identité de jeu du compte actif pour le lancement, en réutilisant les jetons encore valides.
+pub async fn launch_session(app: &AppHandle) -> Result<Session, AuthError> {
+    active_session(&AuthContext::from_app(app)?).await
+}
 
-        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+pub async fn active_session(ctx: &AuthContext) -> Result<Session, AuthError> {
+    let key = active_key(ctx)?;
+    let mut caches = ctx.state.caches.lock().await;
+    let cache = caches.entry(key.clone()).or_default();
+    let (access_token, identity) = identity(ctx, cache, &key, false).await?;
+    if key == REFRESH_KEY {
+        register_account(ctx, &mut caches, &key, &identity.profile)?;
+    } else {
+        let mut index = load_index(ctx)?;
+        index.touch(&identity.profile.id);
+        save_index(ctx, &index)?;
     }
+    Ok(Session {
+        player_name: identity.profile.name,
+        uuid: identity.profile.id,
+        access_token,
+        user_type: "msa".to_string(),
+        xuid: identity.xuid,
+    })
 }
 
 /* ===================== Session utils ===================== */
 #[tauri::command]
 pub fn auth_is_connected(app: AppHandle) -> bool {
-    get_refresh_token(&app).is_ok()
+    AuthContext::from_app(&app)
+        .is_ok_and(|ctx| active_key(&ctx).is_ok_and(|key| get_refresh_token(&ctx, &key).is_ok()))
 }
 
+/// Déconnecte un compte (l'actif par défaut) : refresh token et entrée de la liste supprimés.
+#[tauri::command]
+pub async fn auth_logout(app: AppHandle, uuid: Option<String>) -> Result<(), AuthError> {
+    let ctx = AuthContext::from_app(&app)?;
+    let uuid = match uuid {
+        Some(uuid) => uuid,
+        None => match load_index(&ctx)?.active {
+            Some(uuid) => uuid,
+            None => {
+                // Installation mono-compte jamais identifiée.
+                ctx.state.caches.lock().await.remove(REFRESH_KEY);
+                return delete_refresh_token(&ctx, REFRESH_KEY);
+            }
+        },
+    };
+    remove_account(&ctx, &uuid).await
+}
+
+/* ===================== Comptes ===================== */
+
+/// Comptes connus, du plus récemment utilisé au plus ancien.
 #[tauri::command]
-pub fn auth_logout(app: AppHandle) -> Result<(), String> {
-    delete_refresh_token(&app)
+pub fn auth_list_accounts(app: AppHandle) -> Result<Vec<AccountSummary>, AuthError> {
+    Ok(load_index(&AuthContext::from_app(&app)?)?.summaries())
+}
+
+#[tauri::command]
+pub fn auth_set_active_account(app: AppHandle, uuid: String) -> Result<AccountSummary, AuthError> {
+    let ctx = AuthContext::from_app(&app)?;
+    let mut index = load_index(&ctx)?;
+    if index.get(&uuid).is_none() {
+        return Err(AuthError::UnknownAccount(uuid));
+    }
+    index.active = Some(uuid.clone());
+    index.touch(&uuid);
+    save_index(&ctx, &index)?;
+    index
+        .summaries()
+        .into_iter()
+        .find(|a| a.uuid == uuid)
+        .ok_or(AuthError::UnknownAccount(uuid))
+}
+
+#[tauri::command]
+pub async fn auth_remove_account(app: AppHandle, uuid: String) -> Result<(), AuthError> {
+    remove_account(&AuthContext::from_app(&app)?, &uuid).await
+}
+
+async fn remove_account(ctx: &AuthContext, uuid: &str) -> Result<(), AuthError> {
+    let key = refresh_key(uuid);
+    ctx.state.caches.lock().await.remove(&key);
+    delete_refresh_token(ctx, &key)?;
+    let mut index = load_index(ctx)?;
+    index.remove(uuid);
+    save_index(ctx, &index)
 }
 
 /* ===================== Chaîne Refresh → XBL → XSTS → MC Profile ===================== */
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct McProfileLite {
     pub id: String,
     pub name: String,
     pub skin_url: Option<String>,
+    #[serde(default)]
+    pub skin_variant: Option<SkinVariant>,
+    #[serde(default)]
+    pub capes: Vec<McCape>,
+}
+
+/// Cape possédée ; `state` vaut `ACTIVE` pour celle qui est portée.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct McCape {
+    pub id: String,
+    pub alias: String,
+    pub state: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct MsErrorResp {
+    error: String,
+}
+
+/// Refresh token révoqué/expiré : on oublie la session, le compte reste listé mais marqué
+/// `needs_relogin`, et on demande une reconnexion au front.
+fn require_relogin(ctx: &AuthContext, key: &str) {
+    if let Err(e) = delete_refresh_token(ctx, key) {
+        println!("[auth] require_relogin: delete failed: {e}");
+    }
+    let account = match load_index(ctx) {
+        Ok(mut index) => {
+            let uuid = index.accounts.iter().find(|a| refresh_key(&a.uuid) == key).map(|a| a.uuid.clone());
+            if let Some(uuid) = &uuid {
+                index.mark_relogin(uuid);
+                if let Err(e) = save_index(ctx, &index) {
+                    println!("[auth] require_relogin: index save failed: {e}");
+                }
+            }
+            uuid
+        }
+        Err(e) => {
+            println!("[auth] require_relogin: index load failed: {e}");
+            None
+        }
+    };
+    ctx.emit("auth://relogin", json!({ "reason": "invalid_grant", "account": account }));
 }
 
 /* -- 1) Refresh Microsoft access_token depuis le refresh_token stocké -- */
-async fn ms_refresh_access_token(refresh: &str) -> Result<String, String> {
+/// Microsoft fait tourner le refresh token : le nouveau remplace l'ancien dans le coffre.
+async fn ms_refresh_access_token(ctx: &AuthContext, key: &str, refresh: &str) -> Result<MsTokenRaw, AuthError> {
     println!("[auth] ms_refresh_access_token: start");
     let form = [
         ("grant_type", "refresh_token"),
-        ("client_id", MS_CLIENT_ID),
+        ("client_id", ctx.config.client_id.as_str()),
         ("refresh_token", refresh),
         ("scope", MS_SCOPES), // <-- important
     ];
-    let res = reqwest::Client::new()
-        .post(MS_TOKEN_URL)
+    let res = ctx
+        .http
+        .post(ctx.config.token_url())
         .form(&form)
         .send()
         .await
-        .map_err(|e| { println!("[auth] ms_refresh_access_token http err: {e}"); format!("http error: {e}") })?;
+        .inspect_err(|e| println!("[auth] ms_refresh_access_token http err: {e}"))?;
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
 
     if !status.is_success() {
         println!("[auth] ms_refresh_access_token FAIL {status} – {text}");
-        return Err(format!("ms refresh failed: {status} – {text}"));
+        if serde_json::from_str::<MsErrorResp>(&text).is_ok_and(|e| e.error == "invalid_grant") {
+            require_relogin(ctx, key);
+            return Err(AuthError::InvalidGrant);
+        }
+        return Err(AuthError::Service(format!("ms refresh failed: {status} – {text}")));
     }
-    let raw: MsTokenRaw = serde_json::from_str(&text)
-        .map_err(|e| { println!("[auth] ms_refresh_access_token json err: {e} – body: {text}"); format!("json error: {e} – body: {text}") })?;
+    let raw: MsTokenRaw = serde_json::from_str(&text).map_err(|e| {
+        println!("[auth] ms_refresh_access_token json err: {e} – body: {text}");
+        AuthError::Service(format!("json error: {e} – body: {text}"))
+    })?;
 
     if let Some(s) = jwt_claims_scopes(&raw.access_token) {
         println!("[auth] ms_access.scp = {s}");
     } else {
         println!("[auth] ms_access.scp = <absent>");
     }
-    Ok(raw.access_token)
+    if let Some(rotated) = raw.refresh_token.as_deref().filter(|r| *r != refresh) {
+        save_refresh_token(ctx, key, rotated)?;
+        println!("[auth] ms_refresh_access_token: refresh token rotated");
+    }
+    Ok(raw)
 }
 /* -- 2) XBL user.authenticate -- */
 #[derive(Deserialize)]
 struct XblAuthResp {
     #[serde(rename = "Token")]
     token: String,
+    #[serde(rename = "NotAfter")]
+    not_after: Option<String>,
     #[serde(rename = "DisplayClaims")]
     display_claims: XuiClaims,
 }
+
+/// Jeton Xbox (XBL ou XSTS) avec son `uhs` et sa date `NotAfter`.
+struct XboxToken {
+    token: String,
+    uhs: String,
+    not_after: Option<SystemTime>,
+}
+
+impl XboxToken {
+    fn cached<T: Clone>(&self, value: T) -> Cached<T> {
+        // Sans `NotAfter` lisible, on ne garde pas le jeton.
+        Cached { value, expires_at: self.not_after.unwrap_or(UNIX_EPOCH) }
+    }
+}
+
+/// `2024-05-01T12:34:56.1234567Z` → instant UTC (format des `NotAfter` Xbox).
+fn parse_not_after(s: &str) -> Option<SystemTime> {
+    let (date, time) = s.strip_suffix('Z')?.split_once('T')?;
+    let mut d = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (y, m, day) = (d.next()??, d.next()??, d.next()??);
+    let mut t = time.split('.').next()?.splitn(3, ':').map(|p| p.parse::<u64>().ok());
+    let (hh, mm, ss) = (t.next()??, t.next()??, t.next()??);
+
+    // Jours depuis 1970-01-01 (calendrier grégorien proleptique).
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146_097 + doe - 719_468).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hh * 3_600 + mm * 60 + ss))
+}
 #[derive(Deserialize)]
 struct XuiClaims { xui: Vec<Uhs> }
 #[derive(Deserialize)]
 struct Uhs { uhs: String }
 
-async fn xbl_auth(ms_access_token: &str) -> Result<(String, String), String> {
+/// Échec XBL/XSTS : un XErr reconnu donne sa variante dédiée, sinon l'erreur de l'étape.
+fn xbox_failure(status: reqwest::StatusCode, text: &str, stage: fn(String) -> AuthError) -> AuthError {
+    match serde_json::from_str::<XblError>(text) {
+        Ok(XblError { xerr: Some(xerr), Message: message, .. }) => {
+            AuthError::from_xerr(xerr, message.unwrap_or_default())
+        }
+        _ => stage(format!("{status} – {text}")),
+    }
+}
+
+async fn xbl_auth(ctx: &AuthContext, ms_access_token: &str) -> Result<XboxToken, AuthError> {
     println!("[auth] xbl_auth: start");
     let body = serde_json::json!({
         "Properties": {
-          "AuthMethod": "RPS",
-          "SiteName": "user.auth.xboxlive.com",
-          "RpsTicket": format!("d={}", ms_access_token)
+            "AuthMethod": "RPS",
+            "SiteName": "user.auth.xboxlive.com",
+            "RpsTicket": format!("d={}", ms_access_token)
         },
         "RelyingParty": "http://auth.xboxlive.com",
         "TokenType": "JWT"
     });
-    let client = reqwest::Client::new();
-    let res = client
-        .post("https://user.auth.xboxlive.com/user/authenticate")
+    let res = ctx
+        .http
+        .post(ctx.config.xbl_url())
         .header("Accept", "application/json")
         .header("Content-Type", "application/json")
         .header("x-xbl-contract-version", "1")
         .json(&body)
         .send()
         .await
-        .map_err(|e| { println!("[auth] xbl_auth http err: {e}"); format!("http error: {e}") })?;
+        .inspect_err(|e| println!("[auth] xbl_auth http err: {e}"))?;
 
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
 
     if !status.is_success() {
-        if let Ok(err) = serde_json::from_str::<XblError>(&text) {
-            let x = err.xerr.unwrap_or(0);
-            let hint = match x {
-                2148916233 => "→ Pas de profil Xbox: connecte-toi sur xbox.com et crée un gamertag.",
-                2148916235 => "→ Compte enfant: validation parentale nécessaire.",
-                2148916238 => "→ Compte suspendu/banni.",
-                _ => ""
-            };
-            println!("[auth] xbl_auth FAIL {status} – XErr={x} – {:?} {hint}", err.Message);
-            return Err(format!("xbl auth failed: {status} – XErr={x} – {}", err.Message.unwrap_or_default()));
-        }
-        println!("[auth] xbl_auth FAIL {status} – {text}");
-        return Err(format!("xbl auth failed: {status} – {text}"));
+        let err = xbox_failure(status, &text, AuthError::Xbl);
+        println!("[auth] xbl_auth FAIL {status} – {} – {text}", err.code());
+        return Err(err);
     }
 
     println!("[auth] xbl_auth: success");
-    let parsed: XblAuthResp = serde_json::from_str(&text)
-        .map_err(|e| { println!("[auth] xbl_auth json err: {e} – body: {text}"); format!("json error: {e} – body: {text}") })?;
-    let uhs = parsed.display_claims.xui.get(0).ok_or("xbl: missing uhs")?.uhs.clone();
-    Ok((parsed.token, uhs))
+    let parsed: XblAuthResp = serde_json::from_str(&text).map_err(|e| {
+        println!("[auth] xbl_auth json err: {e} – body: {text}");
+        AuthError::Service(format!("json error: {e} – body: {text}"))
+    })?;
+    let uhs = parsed
+        .display_claims
+        .xui
+        .get(0)
+        .ok_or_else(|| AuthError::Xbl("missing uhs".into()))?
+        .uhs
+        .clone();
+    let not_after = parsed.not_after.as_deref().and_then(parse_not_after);
+    Ok(XboxToken { token: parsed.token, uhs, not_after })
 }
 /* -- 3) XSTS authorize -- */
 #[derive(Deserialize)]
 struct XstsAuthResp {
     #[serde(rename = "Token")]
     token: String,
+    #[serde(rename = "NotAfter")]
+    not_after: Option<String>,
     #[serde(rename = "DisplayClaims")]
     display_claims: XuiClaims,
 }
-async fn xsts_auth(xbl_token: &str) -> Result<(String, String), String> {
+async fn xsts_auth(ctx: &AuthContext, xbl_token: &str) -> Result<XboxToken, AuthError> {
     let body = json!({
-    "Properties": {
-      "SandboxId": "RETAIL",
-      "UserTokens": [xbl_token]
-    },
-    "RelyingParty": "rp://api.minecraftservices.com/",
-    "TokenType": "JWT"
-  });
-    let client = reqwest::Client::new();
-    let res = client
-        .post("https://xsts.auth.xboxlive.com/xsts/authorize")
+        "Properties": {
+            "SandboxId": "RETAIL",
+            "UserTokens": [xbl_token]
+        },
+        "RelyingParty": "rp://api.minecraftservices.com/",
+        "TokenType": "JWT"
+    });
+    let res = ctx
+        .http
+        .post(ctx.config.xsts_url())
         .header("Accept", "application/json")
         .header("Content-Type", "application/json")
         .header("x-xbl-contract-version", "1")
         .json(&body)
         .send()
-        .await
-        .map_err(|e| format!("http error: {e}"))?;
+        .await?;
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
     if !status.is_success() {
-        return Err(format!("xsts auth failed: {status} – {text}"));
+        let err = xbox_failure(status, &text, AuthError::Xsts);
+        println!("[auth] xsts_auth FAIL {status} – {} – {text}", err.code());
+        return Err(err);
     }
     let parsed: XstsAuthResp =
-        serde_json::from_str(&text).map_err(|e| format!("json error: {e} – body: {text}"))?;
-    let uhs = parsed.display_claims.xui.get(0)
-        .ok_or("xsts: missing uhs")?
+        serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
+    let uhs = parsed
+        .display_claims
+        .xui
+        .get(0)
+        .ok_or_else(|| AuthError::Xsts("missing uhs".into()))?
         .uhs
         .clone();
-    Ok((parsed.token, uhs))
+    let not_after = parsed.not_after.as_deref().and_then(parse_not_after);
+    Ok(XboxToken { token: parsed.token, uhs, not_after })
 }
 
 /* -- 4) Minecraft login_with_xbox -- */
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 struct McLoginResp {
     access_token: String,
     expires_in: u64,
     token_type: String,
 }
-async fn mc_login_with_xbox(uhs: &str, xsts: &str) -> Result<McLoginResp, String> {
+async fn mc_login_with_xbox(ctx: &AuthContext, uhs: &str, xsts: &str) -> Result<McLoginResp, AuthError> {
     let body = json!({
-    "identityToken": format!("XBL3.0 x={};{}", uhs, xsts)
-  });
-    let client = reqwest::Client::new();
-    let res = client
-        .post("https://api.minecraftservices.com/authentication/login_with_xbox")
+        "identityToken": format!("XBL3.0 x={};{}", uhs, xsts)
+    });
+    let res = ctx
+        .http
+        .post(ctx.config.mc_login_url())
         .json(&body)
         .send()
-        .await
-        .map_err(|e| format!("http error: {e}"))?;
+        .await?;
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
     if !status.is_success() {
-        return Err(format!("mc login failed: {status} – {text}"));
+        // Client ID pas (encore) validé par Mojang pour l'API Minecraft.
+        if status == reqwest::StatusCode::FORBIDDEN && text.contains("Invalid app registration") {
+            return Err(AuthError::InvalidAppRegistration);
+        }
+        return Err(AuthError::Service(format!("mc login failed: {status} – {text}")));
     }
-    serde_json::from_str(&text).map_err(|e| format!("json error: {e} – body: {text}"))
+    serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))
 }
 
 /* -- 5) Vérifie la licence Minecraft -- */
 #[derive(Deserialize)]
 struct Entitlements { items: Vec<serde_json::Value> }
-async fn mc_check_entitlement(mc_token: &str) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://api.minecraftservices.com/entitlements/mcstore")
+async fn mc_check_entitlement(ctx: &AuthContext, mc_token: &str) -> Result<(), AuthError> {
+    let res = ctx
+        .http
+        .get(ctx.config.entitlements_url())
         .bearer_auth(mc_token)
         .send()
-        .await
-        .map_err(|e| format!("http error: {e}"))?;
+        .await?;
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
     if !status.is_success() {
-        return Err(format!("entitlements failed: {status} – {text}"));
+        return Err(AuthError::Service(format!("entitlements failed: {status} – {text}")));
     }
     let ent: Entitlements =
-        serde_json::from_str(&text).map_err(|e| format!("json error: {e} – body: {text}"))?;
+        serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
     if ent.items.is_empty() {
-        return Err("Aucune licence Minecraft associée à ce compte.".into());
+        return Err(AuthError::NoEntitlement);
     }
     Ok(())
 }
@@ -475,57 +1056,162 @@ struct McProfileRaw {
     id: String,
     name: String,
     skins: Option<Vec<McSkin>>,
+    capes: Option<Vec<McCape>>,
 }
 #[derive(Deserialize)]
-struct McSkin { url: String, state: String }
+struct McSkin { url: String, state: String, variant: Option<SkinVariant> }
 
-async fn mc_fetch_profile(mc_token: &str) -> Result<McProfileLite, String> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://api.minecraftservices.com/minecraft/profile")
+async fn mc_fetch_profile(ctx: &AuthContext, mc_token: &str) -> Result<McProfileLite, AuthError> {
+    let res = ctx
+        .http
+        .get(ctx.config.profile_url())
         .bearer_auth(mc_token)
         .send()
-        .await
-        .map_err(|e| format!("http error: {e}"))?;
+        .await?;
     let status = res.status();
     let text = res.text().await.unwrap_or_default();
+    // Seul ce GET signale par un 404 un compte sans profil Java.
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(AuthError::NoProfile);
+    }
+    profile_response(status, &text, "profile")
+}
+
+/// Profil renvoyé par `/minecraft/profile` et par les modifications de skin (même format).
+pub(super) fn profile_response(status: reqwest::StatusCode, text: &str, what: &str) -> Result<McProfileLite, AuthError> {
     if !status.is_success() {
-        return Err(format!("profile failed: {status} – {text}"));
+        return Err(AuthError::Service(format!("{what} failed: {status} – {text}")));
     }
     let raw: McProfileRaw =
-        serde_json::from_str(&text).map_err(|e| format!("json error: {e} – body: {text}"))?;
+        serde_json::from_str(text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
 
-    let skin_url = raw.skins
-        .and_then(|v| v.into_iter().find(|s| s.state == "ACTIVE"))
-        .map(|s| s.url);
+    let skin = raw.skins.and_then(|v| v.into_iter().find(|s| s.state == "ACTIVE"));
+    let skin_variant = skin.as_ref().and_then(|s| s.variant);
+    let skin_url = skin.map(|s| s.url);
 
-    Ok(McProfileLite { id: raw.id, name: raw.name, skin_url })
+    Ok(McProfileLite {
+        id: raw.id,
+        name: raw.name,
+        skin_url,
+        skin_variant,
+        capes: raw.capes.unwrap_or_default(),
+    })
 }
 
 /* -- 7) Commande publique: tout faire et ne renvoyer que le profil -- */
+/// Profil du compte actif. Les jetons encore valides sont réutilisés ; le profil est
+/// toujours relu (skin à jour) et recopié dans la liste des comptes.
 #[tauri::command]
-pub async fn mc_fetch_profile_from_refresh(app: tauri::AppHandle) -> Result<McProfileLite, String> {
+pub async fn mc_fetch_profile_from_refresh(app: tauri::AppHandle) -> Result<McProfileLite, AuthError> {
+    fetch_profile(&AuthContext::from_app(&app)?).await
+}
+
+/// Cœur de `mc_fetch_profile_from_refresh`.
+pub async fn fetch_profile(ctx: &AuthContext) -> Result<McProfileLite, AuthError> {
     println!("[auth] chain: begin");
-    let refresh = get_refresh_token(&app)
-        .map_err(|_| { println!("[auth] chain: no refresh"); "Aucun refresh token stocké. Veuillez vous connecter.".to_string() })?;
+    let key = active_key(ctx)?;
+    let mut caches = ctx.state.caches.lock().await;
+    let cache = caches.entry(key.clone()).or_default();
+    let (_, identity) = identity(ctx, cache, &key, true).await?;
+    register_account(ctx, &mut caches, &key, &identity.profile)?;
+    Ok(identity.profile)
+}
 
-    println!("[auth] chain: refresh present (len={})", refresh.len());
-    let ms_access = ms_refresh_access_token(&refresh).await?;
-    println!("[auth] chain: got ms_access (len={})", ms_access.len());
+/* ===================== Services de profil (skins, capes) ===================== */
 
-    let (xbl_token, _uhs1) = xbl_auth(&ms_access).await?;
-    println!("[auth] chain: got xbl_token (len={})", xbl_token.len());
+/// Jeton Minecraft du compte actif ; les jetons en cache sont réutilisés.
+pub(super) async fn active_mc_token(ctx: &AuthContext) -> Result<String, AuthError> {
+    let key = active_key(ctx)?;
+    let mut caches = ctx.state.caches.lock().await;
+    let cache = caches.entry(key.clone()).or_default();
+    minecraft_token(ctx, cache, &key).await
+}
 
-    let (xsts_token, uhs) = xsts_auth(&xbl_token).await?;
-    println!("[auth] chain: got xsts (len={}), uhs={}", xsts_token.len(), uhs);
+/// Jeton Minecraft pour un compte sans profil : la création de profil en cours, sinon le compte actif.
+pub(super) async fn profile_setup_token(ctx: &AuthContext) -> Result<String, AuthError> {
+    let key = profile_setup_key(ctx)?;
+    let mut caches = ctx.state.caches.lock().await;
+    let cache = caches.entry(key.clone()).or_default();
+    minecraft_token(ctx, cache, &key).await
+}
 
-    let mc = mc_login_with_xbox(&uhs, &xsts_token).await?;
-    println!("[auth] chain: mc login ok, access len={}", mc.access_token.len());
+/// `PENDING_KEY` seulement si sa création de profil est toujours en cours : une entrée périmée
+/// est supprimée plutôt que de prêter son jeton au compte actif.
+fn profile_setup_key(ctx: &AuthContext) -> Result<String, AuthError> {
+    if ctx.credentials.get(PENDING_KEY).map_err(AuthError::Storage)?.is_some() {
+        if ctx.state.profile_setup_live() {
+            return Ok(PENDING_KEY.to_string());
+        }
+        delete_refresh_token(ctx, PENDING_KEY)?;
+    }
+    active_key(ctx)
+}
+
+/// Recopie un profil modifié dans le cache de session et dans la liste des comptes.
+pub(super) async fn store_profile(ctx: &AuthContext, profile: &McProfileLite) -> Result<(), AuthError> {
+    if let Some(identity) = ctx
+        .state
+        .caches
+        .lock()
+        .await
+        .get_mut(&refresh_key(&profile.id))
+        .and_then(|cache| cache.identity.as_mut())
+    {
+        identity.profile = profile.clone();
+    }
+    let mut index = load_index(ctx)?;
+    if index.get(&profile.id).is_some() {
+        index.upsert(profile);
+        save_index(ctx, &index)?;
+    }
+    Ok(())
+}
+
+/* ===================== Création du profil Java ===================== */
+
+/// Crée le profil Java (pseudo initial) d'un compte qui possède le jeu sans en avoir, puis
+/// termine la connexion : le compte est enregistré et activé.
+#[tauri::command]
+pub async fn mc_ms_create_profile(app: AppHandle, name: String) -> Result<AccountSummary, AuthError> {
+    create_profile(&AuthContext::from_app(&app)?, &name).await
+}
 
-    mc_check_entitlement(&mc.access_token).await?;
-    println!("[auth] chain: entitlement ok");
+pub async fn create_profile(ctx: &AuthContext, name: &str) -> Result<AccountSummary, AuthError> {
+    profile::validate_profile_name(name)?;
+    let key = profile_setup_key(ctx)?;
+    let mut caches = ctx.state.caches.lock().await;
+    let cache = caches.entry(key.clone()).or_default();
+    let token = minecraft_token(ctx, cache, &key).await?;
+
+    let res = ctx
+        .http
+        .post(ctx.config.profile_url())
+        .bearer_auth(&token)
+        .json(&json!({ "profileName": name }))
+        .send()
+        .await?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if status.is_client_error() && profile::name_rejected(&text) {
+        return Err(AuthError::NameUnavailable(name.to_string()));
+    }
+    profile_response(status, &text, "profile creation")?;
 
-    let prof = mc_fetch_profile(&mc.access_token).await?;
-    println!("[auth] chain: profile ok: {} ({})", prof.name, prof.id);
-    Ok(prof)
-}
\ No newline at end of file
+    let (_, identity) = identity(ctx, cache, &key, true).await?;
+    let account = register_account(ctx, &mut caches, &key, &identity.profile)?;
+    if key == PENDING_KEY {
+        ctx.state.set_profile_setup(None);
+    }
+    Ok(account)
+}
+
+/// Abandonne la création de profil : la connexion en attente est oubliée.
+#[tauri::command]
+pub async fn mc_ms_cancel_profile_setup(app: AppHandle) -> Result<(), AuthError> {
+    cancel_profile_setup(&AuthContext::from_app(&app)?).await;
+    Ok(())
+}
+
+pub async fn cancel_profile_setup(ctx: &AuthContext) {
+    discard_profile_setup(ctx, &mut *ctx.state.caches.lock().await);
+}
@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+/* ===================== Configuration de l'authentification ===================== */
+
+/// Client ID par défaut : `KASHIR_MS_CLIENT_ID` au moment de la compilation, sinon l'app Kashir.
+const DEFAULT_CLIENT_ID: &str = match option_env!("KASHIR_MS_CLIENT_ID") {
+    Some(id) => id,
+    None => "e5a244a8-3f50-41fb-b4fb-5b58bf356f5e",
+};
+
+const DEFAULT_MS_LOGIN_BASE: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0";
+const DEFAULT_XBL_BASE: &str = "https://user.auth.xboxlive.com";
+const DEFAULT_XSTS_BASE: &str = "https://xsts.auth.xboxlive.com";
+const DEFAULT_MC_SERVICES_BASE: &str = "https://api.minecraftservices.com";
+/// RFC 8628 §3.5 : `slow_down` ajoute 5 s à l'intervalle de poll.
+const DEFAULT_SLOW_DOWN_SECS: u64 = 5;
+
+/// Nom du fichier de configuration dans le dossier de config de l'app.
+pub const CONFIG_FILE: &str = "launcher.json";
+
+/// Client ID Azure et bases des services appelés par la chaîne d'authentification.
+/// Priorité : variables d'environnement, puis `launcher.json` (section `auth`), puis défauts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub client_id: String,
+    /// `…/oauth2/v2.0` : `devicecode`, `token` et `authorize` y sont ajoutés.
+    pub ms_login_base: String,
+    pub xbl_base: String,
+    pub xsts_base: String,
+    pub mc_services_base: String,
+    /// Ajout à l'intervalle de poll du device code sur `slow_down` ; ni fichier ni variable,
+    /// seuls les tests le réduisent.
+    pub slow_down_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            client_id: DEFAULT_CLIENT_ID.to_string(),
+            ms_login_base: DEFAULT_MS_LOGIN_BASE.to_string(),
+            xbl_base: DEFAULT_XBL_BASE.to_string(),
+            xsts_base: DEFAULT_XSTS_BASE.to_string(),
+            mc_services_base: DEFAULT_MC_SERVICES_BASE.to_string(),
+            slow_down_secs: DEFAULT_SLOW_DOWN_SECS,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LauncherFile {
+    #[serde(default)]
+    auth: AuthFileSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuthFileSection {
+    client_id: Option<String>,
+    ms_login_base: Option<String>,
+    xbl_base: Option<String>,
+    xsts_base: Option<String>,
+    mc_services_base: Option<String>,
+}
+
+impl AuthConfig {
+    /// Configuration effective pour le dossier de config de l'app.
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        Self::from_sources(&config_dir.join(CONFIG_FILE), |name| std::env::var(name).ok())
+    }
+
+    /// `file` absent = pas de surcharge ; `env` lit une variable d'environnement.
+    pub fn from_sources(file: &Path, env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let section = match std::fs::read(file) {
+            Ok(data) => {
+                serde_json::from_slice::<LauncherFile>(&data)
+                    .map_err(|e| format!("{}: {e}", file.display()))?
+                    .auth
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => AuthFileSection::default(),
+            Err(e) => return Err(format!("{}: {e}", file.display())),
+        };
+
+        let mut cfg = Self::default();
+        let fields = [
+            (&mut cfg.client_id, section.client_id, "KASHIR_MS_CLIENT_ID"),
+            (&mut cfg.ms_login_base, section.ms_login_base, "KASHIR_MS_LOGIN_BASE"),
+            (&mut cfg.xbl_base, section.xbl_base, "KASHIR_XBL_BASE"),
+            (&mut cfg.xsts_base, section.xsts_base, "KASHIR_XSTS_BASE"),
+            (&mut cfg.mc_services_base, section.mc_services_base, "KASHIR_MC_SERVICES_BASE"),
+        ];
+        for (value, from_file, var) in fields {
+            let chosen = env(var).or(from_file).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+            if let Some(v) = chosen {
+                *value = v;
+            }
+        }
+        for base in [&mut cfg.ms_login_base, &mut cfg.xbl_base, &mut cfg.xsts_base, &mut cfg.mc_services_base] {
+            reqwest::Url::parse(base).map_err(|e| format!("url invalide {base}: {e}"))?;
+            let trimmed = base.trim_end_matches('/').len();
+            base.truncate(trimmed);
+        }
+        Ok(cfg)
+    }
+
+    pub fn device_code_url(&self) -> String {
+        format!("{}/devicecode", self.ms_login_base)
+    }
+
+    pub fn token_url(&self) -> String {
+        format!("{}/token", self.ms_login_base)
+    }
+
+    pub fn authorize_url(&self) -> String {
+        format!("{}/authorize", self.ms_login_base)
+    }
+
+    pub fn xbl_url(&self) -> String {
+        format!("{}/user/authenticate", self.xbl_base)
+    }
+
+    pub fn xsts_url(&self) -> String {
+        format!("{}/xsts/authorize", self.xsts_base)
+    }
+
+    pub fn mc_login_url(&self) -> String {
+        format!("{}/authentication/login_with_xbox", self.mc_services_base)
+    }
+
+    pub fn entitlements_url(&self) -> String {
+        format!("{}/entitlements/mcstore", self.mc_services_base)
+    }
+
+    pub fn profile_url(&self) -> String {
+        format!("{}/minecraft/profile", self.mc_services_base)
+    }
+
+    pub fn skins_url(&self) -> String {
+        format!("{}/minecraft/profile/skins", self.mc_services_base)
+    }
+
+    pub fn active_skin_url(&self) -> String {
+        format!("{}/minecraft/profile/skins/active", self.mc_services_base)
+    }
+
+    pub fn active_cape_url(&self) -> String {
+        format!("{}/minecraft/profile/capes/active", self.mc_services_base)
+    }
+
+    pub fn name_change_url(&self) -> String {
+        format!("{}/minecraft/profile/namechange", self.mc_services_base)
+    }
+
+    /// `name` doit déjà être validé (il est placé tel quel dans le chemin).
+    pub fn name_available_url(&self, name: &str) -> String {
+        format!("{}/minecraft/profile/name/{name}/available", self.mc_services_base)
+    }
+
+    pub fn name_url(&self, name: &str) -> String {
+        format!("{}/minecraft/profile/name/{name}", self.mc_services_base)
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/* ===================== Erreurs d'authentification ===================== */
+
+/// Erreur de la chaîne Microsoft → Xbox → Minecraft. Côté front :
+/// `{ code, message: { fr, en }, detail }`, où `code` est stable et `detail` sert au diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// Serveur injoignable, coupure réseau, délai dépassé côté HTTP.
+    Network(String),
+    /// Refresh token révoqué ou expiré (`invalid_grant`) : reconnexion nécessaire.
+    InvalidGrant,
+    NotSignedIn,
+    UnknownAccount(String),
+    DeviceCodeExpired,
+    /// L'utilisateur a refusé l'autorisation (device code ou navigateur).
+    Declined,
+    Timeout,
+    Cancelled,
+    /// XErr 2148916233 : compte Microsoft sans profil Xbox.
+    NoXboxAccount,
+    /// XErr 2148916238 (compte enfant hors famille) / 2148916229 (contrôle parental).
+    ChildAccount,
+    /// XErr 2148916227.
+    Banned,
+    /// XErr 2148916235 (pays non couvert), 2148916236 / 2148916237 (vérification d'âge requise).
+    RegionUnavailable,
+    /// Autre XErr renvoyé par XBL ou XSTS.
+    Xbox { xerr: u64, message: String },
+    Xbl(String),
+    Xsts(String),
+    /// 403 `Invalid app registration` : client ID non autorisé par Mojang.
+    InvalidAppRegistration,
+    NoEntitlement,
+    /// Jeu acheté mais aucun profil Java créé (404 sur `/minecraft/profile`).
+    NoProfile,
+    /// Skin refusé avant envoi (format, dimensions, URL).
+    InvalidSkin(String),
+    /// Cape absente du profil.
+    UnknownCape(String),
+    /// Bibliothèque locale de skins (fichiers, index, skin inconnu).
+    SkinLibrary(String),
+    /// Pseudo refusé avant envoi : 3 à 16 caractères `[A-Za-z0-9_]`.
+    InvalidName(String),
+    /// Pseudo déjà pris ou interdit par Mojang.
+    NameUnavailable(String),
+    /// Changement de pseudo impossible pour l'instant (délai de 30 jours).
+    NameChangeNotAllowed,
+    /// Coffre des refresh tokens ou liste des comptes.
+    Storage(String),
+    /// Réponse inattendue d'un service (statut, JSON illisible...).
+    Service(String),
+}
+
+impl AuthError {
+    /// XErr Xbox → variante dédiée quand l'utilisateur peut agir dessus.
+    pub fn from_xerr(xerr: u64, message: String) -> Self {
+        match xerr {
+            2148916233 => Self::NoXboxAccount,
+            2148916227 => Self::Banned,
+            2148916229 | 2148916238 => Self::ChildAccount,
+            2148916235..=2148916237 => Self::RegionUnavailable,
+            _ => Self::Xbox { xerr, message },
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    pub fn message_fr(&self) -> &'static str {
+        self.describe().1
+    }
+
+    pub fn message_en(&self) -> &'static str {
+        self.describe().2
+    }
+
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Self::Network(d)
+            | Self::UnknownAccount(d)
+            | Self::Xbl(d)
+            | Self::Xsts(d)
+            | Self::InvalidSkin(d)
+            | Self::UnknownCape(d)
+            | Self::SkinLibrary(d)
+            | Self::InvalidName(d)
+            | Self::NameUnavailable(d)
+            | Self::Storage(d)
+            | Self::Service(d) => Some(d.clone()),
+            Self::Xbox { xerr, message } => Some(format!("XErr={xerr} – {message}")),
+            _ => None,
+        }
+    }
+
+    /// (code, message FR, message EN)
+    fn describe(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::Network(_) => (
+                "network",
+                "Impossible de joindre les serveurs Microsoft. Vérifiez votre connexion.",
+                "Could not reach Microsoft servers. Check your connection.",
+            ),
+            Self::InvalidGrant => (
+                "invalid_grant",
+                "Session Microsoft expirée. Veuillez vous reconnecter.",
+                "Your Microsoft session has expired. Please sign in again.",
+            ),
+            Self::NotSignedIn => (
+                "not_signed_in",
+                "Aucun compte connecté. Veuillez vous connecter.",
+                "No account signed in. Please sign in.",
+            ),
+            Self::UnknownAccount(_) => ("unknown_account", "Compte inconnu.", "Unknown account."),
+            Self::DeviceCodeExpired => (
+                "device_code_expired",
+                "Le code de connexion a expiré. Recommencez.",
+                "The sign-in code has expired. Please start again.",
+            ),
+            Self::Declined => (
+                "declined",
+                "La connexion a été refusée.",
+                "The sign-in was declined.",
+            ),
+            Self::Timeout => (
+                "timeout",
+                "Délai de connexion dépassé.",
+                "The sign-in timed out.",
+            ),
+            Self::Cancelled => ("cancelled", "Connexion annulée.", "Sign-in cancelled."),
+            Self::NoXboxAccount => (
+                "no_xbox_account",
+                "Ce compte Microsoft n'a pas de profil Xbox. Créez-en un sur xbox.com puis réessayez.",
+                "This Microsoft account has no Xbox profile. Create one on xbox.com and try again.",
+            ),
+            Self::ChildAccount => (
+                "child_account",
+                "Compte enfant : un adulte doit l'ajouter à une famille Microsoft et autoriser le jeu.",
+                "Child account: an adult must add it to a Microsoft family and allow the game.",
+            ),
+            Self::Banned => (
+                "banned",
+                "Ce compte Xbox est suspendu.",
+                "This Xbox account is banned.",
+            ),
+            Self::RegionUnavailable => (
+                "region_unavailable",
+                "Xbox Live n'est pas disponible dans votre pays ou exige une vérification d'âge.",
+                "Xbox Live is not available in your country or requires age verification.",
+            ),
+            Self::Xbox { .. } => (
+                "xbox_error",
+                "Xbox Live a refusé la connexion.",
+                "Xbox Live rejected the sign-in.",
+            ),
+            Self::Xbl(_) => (
+                "xbl_failed",
+                "Échec de l'authentification Xbox Live.",
+                "Xbox Live authentication failed.",
+            ),
+            Self::Xsts(_) => (
+                "xsts_failed",
+                "Échec de l'autorisation Xbox (XSTS).",
+                "Xbox authorization (XSTS) failed.",
+            ),
+            Self::InvalidAppRegistration => (
+                "invalid_app_registration",
+                "Ce launcher n'est pas encore autorisé par Mojang à se connecter à Minecraft.",
+                "This launcher is not yet approved by Mojang to sign in to Minecraft.",
+            ),
+            Self::NoEntitlement => (
+                "no_entitlement",
+                "Aucune licence Minecraft associée à ce compte.",
+                "This account does not own Minecraft.",
+            ),
+            Self::NoProfile => (
+                "no_profile",
+                "Ce compte possède le jeu mais n'a pas encore de profil Java : choisissez un pseudo pour le créer.",
+                "This account owns the game but has no Java profile yet: choose a name to create it.",
+            ),
+            Self::InvalidSkin(_) => (
+                "invalid_skin",
+                "Skin invalide : une image PNG de 64x64 ou 64x32 pixels est attendue.",
+                "Invalid skin: a 64x64 or 64x32 PNG image is expected.",
+            ),
+            Self::UnknownCape(_) => (
+                "unknown_cape",
+                "Ce compte ne possède pas cette cape.",
+                "This account does not own this cape.",
+            ),
+            Self::SkinLibrary(_) => (
+                "skin_library",
+                "Impossible d'accéder à la bibliothèque de skins.",
+                "Could not access the skin library.",
+            ),
+            Self::InvalidName(_) => (
+                "invalid_name",
+                "Pseudo invalide : 3 à 16 caractères, lettres, chiffres et _ uniquement.",
+                "Invalid name: 3 to 16 characters, letters, digits and _ only.",
+            ),
+            Self::NameUnavailable(_) => (
+                "name_unavailable",
+                "Ce pseudo est déjà pris ou n'est pas autorisé.",
+                "This name is already taken or not allowed.",
+            ),
+            Self::NameChangeNotAllowed => (
+                "name_change_not_allowed",
+                "Le pseudo ne peut pas encore être changé (un changement tous les 30 jours).",
+                "The name cannot be changed yet (one change every 30 days).",
+            ),
+            Self::Storage(_) => (
+                "storage",
+                "Impossible d'accéder aux identifiants enregistrés.",
+                "Could not access saved credentials.",
+            ),
+            Self::Service(_) => (
+                "service",
+                "Réponse inattendue d'un service de connexion.",
+                "Unexpected response from a sign-in service.",
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{} ({detail})", self.message_fr()),
+            None => f.write_str(self.message_fr()),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Network(e.to_string())
+    }
+}
+
+#[derive(Serialize)]
+struct Messages {
+    fr: &'static str,
+    en: &'static str,
+}
+
+impl Serialize for AuthError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AuthError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &Messages { fr: self.message_fr(), en: self.message_en() })?;
+        s.serialize_field("detail", &self.detail())?;
+        s.end()
+    }
+}
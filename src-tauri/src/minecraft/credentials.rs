@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+/* ===================== Noms dans le trousseau ===================== */
+
+pub const SERVICE: &str = "ks";
+/// Clé historique du refresh token (compte unique).
+pub const REFRESH_KEY: &str = "ksrefresh";
+#[cfg(windows)]
+const TARGET: &str = "ksmain"; // court et stable (= target_name côté WinCred)
+
+/* ===================== Contrat ===================== */
+
+/// Coffre de secrets clé → valeur (refresh tokens). `get` renvoie `Ok(None)` si la clé est absente,
+/// `delete` d'une clé absente n'est pas une erreur.
+pub trait CredentialStore: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, key: &str, secret: &str) -> Result<(), String>;
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/* ===================== Windows : Credential Manager ===================== */
+
+#[cfg(windows)]
+pub struct WinCredStore;
+
+#[cfg(windows)]
+impl WinCredStore {
+    fn credential(key: &str) -> Result<keyring::windows::WinCredential, String> {
+        // La clé historique garde son target d'origine pour retrouver les sessions existantes.
+        let target = if key == REFRESH_KEY { TARGET.to_string() } else { format!("{TARGET}.{key}") };
+        keyring::windows::WinCredential::new_with_target(Some(&target), SERVICE, key).map_err(|e| {
+            println!("[wincred] new_with_target ERR: {:?} / {}", e, e);
+            e.to_string()
+        })
+    }
+}
+
+#[cfg(windows)]
+impl CredentialStore for WinCredStore {
+    fn name(&self) -> &'static str {
+        "wincred"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        use keyring::credential::CredentialApi;
+        match Self::credential(key)?.get_password() {
+            Ok(s) => Ok(Some(s)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        use keyring::credential::CredentialApi;
+        Self::credential(key)?.set_password(secret).map_err(|e| e.to_string())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        use keyring::credential::CredentialApi;
+        match Self::credential(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/* ===================== Linux (Secret Service + keyutils) / macOS (Keychain) ===================== */
+
+/// Trousseau natif via `keyring::Entry` : Secret Service persistant avec cache keyutils
+/// sous Linux, Keychain sous macOS.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub struct KeyringStore;
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl KeyringStore {
+    fn entry(key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, key).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl CredentialStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        if cfg!(target_os = "macos") {
+            "keychain"
+        } else {
+            "secret-service"
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        match Self::entry(key)?.get_password() {
+            Ok(s) => Ok(Some(s)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        Self::entry(key)?.set_password(secret).map_err(|e| e.to_string())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/* ===================== Repli : fichier chiffré ===================== */
+
+const NONCE_LEN: usize = 24;
+
+/// Un fichier `<clé>.bin` par secret : nonce XChaCha20 puis texte chiffré, la clé servant de
+/// données associées. La clé de chiffrement est le secret d'appareil de `security.rs`.
+///
+/// Ce repli ne sert que sans trousseau natif : la clé est alors elle-même un fichier en clair
+/// (`device_secret.b64`, 0600) du même dossier de données. Il protège d'une copie isolée du
+/// dossier `credentials/`, pas d'un processus qui lit les fichiers de l'utilisateur.
+pub struct EncryptedFileStore {
+    dir: PathBuf,
+    cipher: XChaCha20Poly1305,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: PathBuf, key: &[u8; 32]) -> Self {
+        Self {
+            dir,
+            cipher: XChaCha20Poly1305::new(key.into()),
+        }
+    }
+
+    /// Clé de chiffrement à partir du secret d'appareil (32 octets en base64).
+    pub fn from_device_secret(dir: PathBuf, secret: &str) -> Result<Self, String> {
+        let raw = general_purpose::STANDARD_NO_PAD
+            .decode(secret.trim_end_matches('='))
+            .map_err(|e| format!("device secret: {e}"))?;
+        let key: [u8; 32] = raw
+            .try_into()
+            .map_err(|_| "device secret: 32 octets attendus".to_string())?;
+        Ok(Self::new(dir, &key))
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        let valid = !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !key.starts_with('.');
+        if !valid {
+            return Err(format!("clé de coffre invalide: {key}"));
+        }
+        Ok(self.dir.join(format!("{key}.bin")))
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let path = self.path(key)?;
+        let data = match std::fs::read(&path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+        if data.len() < NONCE_LEN {
+            return Err(format!("{}: fichier tronqué", path.display()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plain = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: key.as_bytes() })
+            .map_err(|_| format!("{}: déchiffrement impossible", path.display()))?;
+        String::from_utf8(plain).map(Some).map_err(|e| e.to_string())
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        let path = self.path(key)?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: secret.as_bytes(), aad: key.as_bytes() })
+            .map_err(|e| e.to_string())?;
+
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("{}: {e}", self.dir.display()))?;
+        // Écriture puis renommage : jamais de fichier à moitié écrit.
+        let tmp = path.with_extension("bin.tmp");
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        std::fs::write(&tmp, &data).map_err(|e| format!("{}: {e}", tmp.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600));
+        }
+        std::fs::rename(&tmp, &path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let path = self.path(key)?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+}
+
+/* ===================== Mémoire (tests, sessions éphémères) ===================== */
+
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl CredentialStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        self.entries.lock().unwrap().insert(key.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/* ===================== Sélection par plateforme ===================== */
+
+/// Coffre actif, géré par Tauri.
+#[derive(Clone)]
+pub struct Credentials {
+    store: Arc<dyn CredentialStore>,
+    /// Raison pour laquelle le trousseau natif a été écarté au profit du fichier chiffré.
+    fallback: Option<String>,
+}
+
+/// Coffre utilisé, pour l'écran des paramètres : `fallback` renseigné si le trousseau natif a été écarté.
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialStatus {
+    pub store: &'static str,
+    pub fallback: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(store: Arc<dyn CredentialStore>) -> Self {
+        Self { store, fallback: None }
+    }
+
+    /// Coffre de repli ; `reason` est l'erreur renvoyée par le trousseau natif.
+    pub fn with_fallback(store: Arc<dyn CredentialStore>, reason: String) -> Self {
+        Self { store, fallback: Some(reason) }
+    }
+
+    /// Trousseau natif de la plateforme ; fichier chiffré si celui-ci ne répond pas
+    /// (pas de démon Secret Service, session sans trousseau déverrouillé...).
+    /// Le repli est exposé par [`auth_credential_status`].
+    pub fn for_platform(app: &AppHandle) -> Result<Self, String> {
+        let mut fallback = None;
+        if let Some(native) = native_store() {
+            match native.get(REFRESH_KEY) {
+                Ok(_) => return Ok(Self::new(native)),
+                Err(e) => fallback = Some(format!("trousseau {} indisponible : {e}", native.name())),
+            }
+        }
+        let dir = app
+            .path()
+            .app_local_data_dir()
+            .map_err(|e| e.to_string())?
+            .join("credentials");
+        let secret = crate::security::device_secret(app)?;
+        let store = Arc::new(EncryptedFileStore::from_device_secret(dir, &secret)?);
+        Ok(match fallback {
+            Some(reason) => Self::with_fallback(store, reason),
+            None => Self::new(store),
+        })
+    }
+
+    pub fn status(&self) -> CredentialStatus {
+        CredentialStatus { store: self.store.name(), fallback: self.fallback.clone() }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let res = self.store.get(key);
+        match &res {
+            Ok(Some(_)) => println!("[cred] {} get {key}: present", self.store.name()),
+            Ok(None) => println!("[cred] {} get {key}: absent", self.store.name()),
+            Err(e) => println!("[cred] {} get {key} ERR: {e}", self.store.name()),
+        }
+        res
+    }
+
+    pub fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        println!("[cred] {} set {key}", self.store.name());
+        self.store.set(key, secret)
+    }
+
+    pub fn delete(&self, key: &str) -> Result<(), String> {
+        println!("[cred] {} delete {key}", self.store.name());
+        self.store.delete(key)
+    }
+}
+
+/// Coffre actif et éventuel repli sur le fichier chiffré.
+#[tauri::command]
+pub fn auth_credential_status(credentials: State<'_, Credentials>) -> CredentialStatus {
+    credentials.status()
+}
+
+#[cfg(windows)]
+fn native_store() -> Option<Arc<dyn CredentialStore>> {
+    Some(Arc::new(WinCredStore))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn native_store() -> Option<Arc<dyn CredentialStore>> {
+    Some(Arc::new(KeyringStore))
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn native_store() -> Option<Arc<dyn CredentialStore>> {
+    None
+}
@@ -0,0 +1,396 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinSet;
+
+use super::McError;
+
+/* ===================== Tâches ===================== */
+
+/// Un fichier attendu sur disque, avec son empreinte SHA-1 et sa taille.
+#[derive(Debug, Clone)]
+pub struct FileTask {
+    pub url: String,
+    pub path: PathBuf,
+    pub sha1: String,
+    pub size: u64,
+}
+
+/// Progression agrégée : `(octets faits, octets attendus)`.
+pub type ProgressFn = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/* ===================== Annulation ===================== */
+
+/// Jeton d'annulation partagé entre une commande et la tâche de lancement.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// `Err(Cancelled)` si l'annulation a été demandée.
+    pub fn check(&self) -> Result<(), McError> {
+        if self.is_cancelled() {
+            Err(McError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Se termine dès que `cancel` est appelé (jamais sinon).
+    pub async fn cancelled(&self) {
+        loop {
+            // Créé avant le test : `notify_waiters` ne peut pas être manqué.
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/* ===================== Helpers SHA-1 / IO ===================== */
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+    hex(&Sha1::digest(data))
+}
+
+/// `true` si le fichier existe et correspond à l'empreinte attendue.
+pub async fn file_matches(path: &Path, sha1: &str) -> bool {
+    match tokio::fs::read(path).await {
+        Ok(data) => sha1_hex(&data).eq_ignore_ascii_case(sha1),
+        Err(_) => false,
+    }
+}
+
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut s = OsString::from(path.as_os_str());
+    s.push(".part");
+    PathBuf::from(s)
+}
+
+fn io_err(path: &Path, e: std::io::Error) -> McError {
+    McError::Io(format!("{}: {e}", path.display()))
+}
+
+/* ===================== Erreurs réessayables ===================== */
+
+enum Attempt {
+    /// Erreur transitoire (5xx, 429, timeout, connexion, hash corrompu) : on réessaie.
+    Retry(McError),
+    Fatal(McError),
+}
+
+fn classify_reqwest(e: reqwest::Error) -> Attempt {
+    let msg = McError::Http(e.to_string());
+    if e.is_timeout() || e.is_connect() || e.is_request() || e.is_body() || e.is_decode() {
+        Attempt::Retry(msg)
+    } else {
+        Attempt::Fatal(msg)
+    }
+}
+
+fn classify_status(status: StatusCode, url: &str) -> Attempt {
+    let msg = McError::Http(format!("{status} – {url}"));
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        Attempt::Retry(msg)
+    } else {
+        Attempt::Fatal(msg)
+    }
+}
+
+/* ===================== Progression agrégée ===================== */
+
+struct Tally {
+    done: AtomicU64,
+    total: u64,
+    on_progress: ProgressFn,
+}
+
+impl Tally {
+    fn add(&self, n: u64) {
+        let now = self.done.fetch_add(n, Ordering::Relaxed) + n;
+        (self.on_progress)(now, self.total);
+    }
+}
+
+/// Octets comptés pendant une tentative, retirés du total si elle échoue.
+struct AttemptTally<'a> {
+    tally: &'a Tally,
+    counted: AtomicU64,
+}
+
+impl AttemptTally<'_> {
+    fn add(&self, n: u64) {
+        self.counted.fetch_add(n, Ordering::Relaxed);
+        self.tally.add(n);
+    }
+
+    fn rollback(&self) {
+        let n = self.counted.swap(0, Ordering::Relaxed);
+        self.tally.done.fetch_sub(n, Ordering::Relaxed);
+    }
+}
+
+/* ===================== Moteur ===================== */
+
+/// Téléchargements parallèles bornés, reprise `Range` des `.part`, vérification SHA-1/taille,
+/// backoff exponentiel et renommage atomique une fois le fichier validé.
+#[derive(Clone)]
+pub struct Downloader {
+    client: reqwest::Client,
+    concurrency: usize,
+    max_retries: u32,
+    base_delay: Duration,
+    cancel: CancelToken,
+}
+
+impl Downloader {
+    pub fn new() -> Result<Self, McError> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .read_timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| McError::Http(e.to_string()))?;
+        Ok(Self::with_client(client))
+    }
+
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            concurrency: 8,
+            max_retries: 4,
+            base_delay: Duration::from_millis(500),
+            cancel: CancelToken::default(),
+        }
+    }
+
+    /// Rend les téléchargements interruptibles par `cancel`.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Nombre maximal de fichiers téléchargés en même temps (au moins 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Nouvelles tentatives après une erreur transitoire, en plus de la première.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Délai avant la première nouvelle tentative, doublé ensuite.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    async fn backoff(&self, attempt: u32) {
+        tokio::time::sleep(self.base_delay * 2u32.pow(attempt.min(6))).await;
+    }
+
+    /// GET complet en mémoire (manifestes, JSON), avec la même politique de retry.
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>, McError> {
+        tokio::select! {
+            res = self.fetch_with_retry(url) => res,
+            _ = self.cancel.cancelled() => Err(McError::Cancelled),
+        }
+    }
+
+    async fn fetch_with_retry(&self, url: &str) -> Result<Vec<u8>, McError> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.client.get(url).send().await {
+                Ok(resp) if resp.status().is_success() => match resp.bytes().await {
+                    Ok(b) => return Ok(b.to_vec()),
+                    Err(e) => classify_reqwest(e),
+                },
+                Ok(resp) => classify_status(resp.status(), url),
+                Err(e) => classify_reqwest(e),
+            };
+            match result {
+                Attempt::Retry(e) if attempt >= self.max_retries => return Err(e),
+                Attempt::Retry(_) => {
+                    self.backoff(attempt).await;
+                    attempt += 1;
+                }
+                Attempt::Fatal(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Télécharge toutes les tâches manquantes ou corrompues. Les fichiers déjà valides
+    /// comptent dans la progression. S'arrête à la première erreur définitive ; en cas
+    /// d'annulation, les `.part` entamés sont supprimés.
+    pub async fn download_all(&self, tasks: Vec<FileTask>, on_progress: ProgressFn) -> Result<(), McError> {
+        self.cancel.check()?;
+        let total: u64 = tasks.iter().map(|t| t.size).sum();
+        let parts: Vec<PathBuf> = tasks.iter().map(|t| part_path(&t.path)).collect();
+        let tally = Arc::new(Tally {
+            done: AtomicU64::new(0),
+            total,
+            on_progress: on_progress.clone(),
+        });
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut set = JoinSet::new();
+
+        for task in tasks {
+            let (this, tally, permits) = (self.clone(), tally.clone(), permits.clone());
+            set.spawn(async move {
+                let _permit = permits.acquire_owned().await.map_err(|e| McError::Io(e.to_string()))?;
+                this.download_one(&task, &tally).await
+            });
+        }
+
+        loop {
+            let joined = tokio::select! {
+                joined = set.join_next() => joined,
+                _ = self.cancel.cancelled() => {
+                    // Attend l'arrêt effectif des tâches avant de nettoyer derrière elles.
+                    set.shutdown().await;
+                    for part in &parts {
+                        let _ = tokio::fs::remove_file(part).await;
+                    }
+                    return Err(McError::Cancelled);
+                }
+            };
+            let Some(joined) = joined else {
+                break;
+            };
+            // Le drop du JoinSet annule les téléchargements restants.
+            joined.map_err(|e| McError::Io(e.to_string()))??;
+        }
+        on_progress(total, total);
+        Ok(())
+    }
+
+    async fn download_one(&self, task: &FileTask, tally: &Tally) -> Result<(), McError> {
+        if file_matches(&task.path, &task.sha1).await {
+            tally.add(task.size);
+            return Ok(());
+        }
+        if let Some(dir) = task.path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| io_err(dir, e))?;
+        }
+
+        let mut attempt = 0;
+        loop {
+            let progress = AttemptTally { tally, counted: AtomicU64::new(0) };
+            match self.try_download(task, &progress).await {
+                Ok(()) => return Ok(()),
+                Err(Attempt::Retry(e)) if attempt >= self.max_retries => return Err(e),
+                Err(Attempt::Retry(_)) => {
+                    progress.rollback();
+                    self.backoff(attempt).await;
+                    attempt += 1;
+                }
+                Err(Attempt::Fatal(e)) => return Err(e),
+            }
+        }
+    }
+
+    async fn try_download(&self, task: &FileTask, progress: &AttemptTally<'_>) -> Result<(), Attempt> {
+        let part = part_path(&task.path);
+        let fatal_io = |p: &Path, e: std::io::Error| Attempt::Fatal(io_err(p, e));
+
+        // Reprise : on re-hache le début déjà présent du `.part`.
+        let mut hasher = Sha1::new();
+        let mut offset = 0u64;
+        if let Ok(mut existing) = tokio::fs::File::open(&part).await {
+            let len = existing.metadata().await.map(|m| m.len()).unwrap_or(0);
+            if len > 0 && len < task.size {
+                let mut buf = vec![0u8; 64 * 1024];
+                loop {
+                    let n = existing.read(&mut buf).await.map_err(|e| fatal_io(&part, e))?;
+                    if n == 0 {
+                        break;
+                    }
+                    hasher.update(&buf[..n]);
+                }
+                offset = len;
+            }
+        }
+
+        let mut req = self.client.get(&task.url);
+        if offset > 0 {
+            req = req.header(RANGE, format!("bytes={offset}-"));
+        }
+        let mut resp = req.send().await.map_err(classify_reqwest)?;
+        let status = resp.status();
+        if !status.is_success() {
+            if status == StatusCode::RANGE_NOT_SATISFIABLE {
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(Attempt::Retry(McError::Http(format!("{status} – {}", task.url))));
+            }
+            return Err(classify_status(status, &task.url));
+        }
+
+        // 206 = reprise acceptée ; 200 = le serveur renvoie tout, on repart de zéro.
+        let mut file = if offset > 0 && status == StatusCode::PARTIAL_CONTENT {
+            progress.add(offset);
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(&part)
+                .await
+                .map_err(|e| fatal_io(&part, e))?
+        } else {
+            hasher = Sha1::new();
+            tokio::fs::File::create(&part).await.map_err(|e| fatal_io(&part, e))?
+        };
+
+        while let Some(chunk) = resp.chunk().await.map_err(classify_reqwest)? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(|e| fatal_io(&part, e))?;
+            progress.add(chunk.len() as u64);
+        }
+        file.flush().await.map_err(|e| fatal_io(&part, e))?;
+        drop(file);
+
+        let written = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+        let got = hex(&hasher.finalize());
+        if written != task.size || !got.eq_ignore_ascii_case(&task.sha1) {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(Attempt::Retry(McError::Integrity(format!(
+                "{}: sha1 attendu {} ({} o), obtenu {got} ({written} o)",
+                task.path.display(),
+                task.sha1,
+                task.size
+            ))));
+        }
+        tokio::fs::rename(&part, &task.path)
+            .await
+            .map_err(|e| fatal_io(&task.path, e))
+    }
+}
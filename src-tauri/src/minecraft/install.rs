@@ -0,0 +1,210 @@
+use std::path::{Component, Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use super::args::log_config_path;
+use super::assets::{self, AssetIndex, AssetLayout};
+use super::download::{sha1_hex, Downloader, FileTask};
+use super::launch::{emit_progress, progress_range};
+use super::natives::native_artifact;
+use super::rules::{library_applies, Platform};
+use super::version::VersionJson;
+use super::{ManifestRoot, McError, VERSION_MANIFEST_URL};
+
+/* ===================== Dossier de jeu géré ===================== */
+
+/// Arborescence standard du launcher officiel, sous le dossier de données de l'app.
+#[derive(Debug, Clone)]
+pub struct GameDir {
+    root: PathBuf,
+}
+
+impl GameDir {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn from_app(app: &AppHandle) -> Result<Self, McError> {
+        let root = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| McError::Io(e.to_string()))?
+            .join("minecraft");
+        Ok(Self::new(root))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn version_dir(&self, id: &str) -> PathBuf {
+        self.root.join("versions").join(id)
+    }
+
+    pub fn version_json(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{id}.json"))
+    }
+
+    pub fn client_jar(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{id}.jar"))
+    }
+
+    pub fn libraries(&self) -> PathBuf {
+        self.root.join("libraries")
+    }
+
+    pub fn assets(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+}
+
+/// Chemin relatif sûr (pas de `..` ni de racine) pour un nom venant d'un manifeste.
+pub(super) fn safe_relative(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| path.to_path_buf())
+}
+
+/* ===================== Plan de téléchargement ===================== */
+
+/// Jar client + librairies applicables (et leurs natives) + config log4j + objets d'assets.
+pub fn plan_files(
+    dirs: &GameDir,
+    version: &VersionJson,
+    index: &AssetIndex,
+    platform: &Platform,
+) -> Vec<FileTask> {
+    let mut tasks = vec![FileTask {
+        url: version.downloads.client.url.clone(),
+        path: dirs.client_jar(&version.id),
+        sha1: version.downloads.client.sha1.clone(),
+        size: version.downloads.client.size,
+    }];
+
+    for lib in version.libraries.iter().filter(|l| library_applies(l, platform)) {
+        let artifact = lib.downloads.as_ref().and_then(|d| d.artifact.as_ref());
+        for artifact in artifact.into_iter().chain(native_artifact(lib, platform)) {
+            tasks.push(FileTask {
+                url: artifact.url.clone(),
+                path: dirs.libraries().join(&artifact.path),
+                sha1: artifact.sha1.clone(),
+                size: artifact.size,
+            });
+        }
+    }
+
+    if let Some(log) = version.logging.as_ref().and_then(|l| l.client.as_ref()) {
+        tasks.push(FileTask {
+            url: log.file.url.clone(),
+            path: log_config_path(dirs, &log.file.id),
+            sha1: log.file.sha1.clone(),
+            size: log.file.size,
+        });
+    }
+
+    tasks.extend(assets::object_tasks(dirs, index));
+    tasks
+}
+
+/* ===================== Téléchargements ===================== */
+
+/// Lit un petit fichier JSON en cache s'il est intègre, sinon le (re)télécharge.
+pub(super) async fn cached_json(
+    dl: &Downloader,
+    url: &str,
+    path: &Path,
+    sha1: Option<&str>,
+) -> Result<Vec<u8>, McError> {
+    if let Ok(data) = tokio::fs::read(path).await {
+        if sha1.is_none_or(|h| sha1_hex(&data).eq_ignore_ascii_case(h)) {
+            return Ok(data);
+        }
+    }
+    let data = dl.fetch(url).await?;
+    if let Some(h) = sha1 {
+        let got = sha1_hex(&data);
+        if !got.eq_ignore_ascii_case(h) {
+            return Err(McError::Integrity(format!("{url}: sha1 attendu {h}, obtenu {got}")));
+        }
+    }
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await.map_err(|e| io_err(dir, e))?;
+    }
+    tokio::fs::write(path, &data).await.map_err(|e| io_err(path, e))?;
+    Ok(data)
+}
+
+fn io_err(path: &Path, e: std::io::Error) -> McError {
+    McError::Io(format!("{}: {e}", path.display()))
+}
+
+/* ===================== Pipeline ===================== */
+
+/// JSON d'une version déjà installée, sans réseau ; `None` s'il manque ou ne se lit pas.
+pub fn installed_version(dirs: &GameDir, id: &str) -> Option<VersionJson> {
+    let data = std::fs::read(dirs.version_json(id)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Résout le JSON de version : une version nommée déjà installée est lue sur disque (lancement
+/// hors ligne) ; sinon via le manifeste (dernière release si `version_id` est vide).
+pub async fn resolve_version(
+    dl: &Downloader,
+    dirs: &GameDir,
+    version_id: Option<&str>,
+) -> Result<VersionJson, McError> {
+    if let Some(version) = version_id.and_then(|id| installed_version(dirs, id)) {
+        return Ok(version);
+    }
+    let manifest = dl.fetch(VERSION_MANIFEST_URL).await?;
+    let root: ManifestRoot =
+        serde_json::from_slice(&manifest).map_err(|e| McError::Json(e.to_string()))?;
+
+    let id = version_id.unwrap_or(&root.latest.release);
+    let entry = root
+        .versions
+        .iter()
+        .find(|v| v.id == id)
+        .ok_or_else(|| McError::UnknownVersion(id.to_string()))?;
+
+    let data = cached_json(dl, &entry.url, &dirs.version_json(id), Some(&entry.sha1)).await?;
+    serde_json::from_slice(&data).map_err(|e| McError::Json(e.to_string()))
+}
+
+/// Version installée, avec la disposition d'assets attendue par le jeu.
+#[derive(Debug, Clone)]
+pub struct InstalledVersion {
+    pub version: VersionJson,
+    pub asset_layout: AssetLayout,
+}
+
+/// Installe (ou répare) une version vanilla : jar client, librairies, objets d'assets et,
+/// pour les anciennes versions, l'arborescence `virtual`/`resources`.
+pub async fn install_version(
+    app: &AppHandle,
+    dl: &Downloader,
+    version_id: Option<&str>,
+) -> Result<InstalledVersion, McError> {
+    let dirs = GameDir::from_app(app)?;
+
+    emit_progress(app, "résolution", 5, Some("manifeste"));
+    let version = resolve_version(dl, &dirs, version_id).await?;
+    emit_progress(app, "résolution", 10, Some("index d'assets"));
+    let index = assets::fetch_asset_index(dl, &dirs, &version).await?;
+
+    let tasks = plan_files(&dirs, &version, &index, &Platform::current());
+    dl.download_all(tasks, progress_range(app, "téléchargement", 10, 85)).await?;
+
+    let asset_layout = index.layout();
+    if asset_layout != AssetLayout::Hashed {
+        emit_progress(app, "assets", 86, Some("arborescence historique"));
+        let (dirs, index_id) = (dirs.clone(), version.asset_index.id.clone());
+        tokio::task::spawn_blocking(move || assets::materialize(&dirs, &index_id, &index))
+            .await
+            .map_err(|e| McError::Io(e.to_string()))??;
+    }
+    emit_progress(app, "vérification", 87, Some("intégrité"));
+
+    Ok(InstalledVersion { version, asset_layout })
+}
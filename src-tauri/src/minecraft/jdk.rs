@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use super::download::Downloader;
+use super::install::{self, GameDir};
+use super::rules::{Arch, OsName, Platform};
+use super::runtime;
+
+#[cfg(windows)]
+const JAVA_EXE: &str = "java.exe";
+#[cfg(not(windows))]
+const JAVA_EXE: &str = "java";
+
+/* ===================== Résultat exposé au front ===================== */
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedJdk {
+    /// Chemin de l'exécutable `java`.
+    pub path: String,
+    pub home: String,
+    pub version: String,
+    pub major: u32,
+    pub arch: String,
+    pub vendor: Option<String>,
+    /// Runtime téléchargé par le launcher (dossier `runtime/`).
+    pub managed: bool,
+    /// Satisfait `javaVersion.majorVersion` de la version choisie (et l'arch de la machine).
+    pub compatible: bool,
+}
+
+/* ===================== Parsing ===================== */
+
+/// `1.8.0_382` → 8, `17.0.8` → 17, `21` → 21, `22-ea` → 22.
+pub fn parse_major(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty());
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
+pub fn parse_arch(arch: &str) -> Option<Arch> {
+    match arch.trim().to_ascii_lowercase().as_str() {
+        "amd64" | "x86_64" | "x64" => Some(Arch::X86_64),
+        "x86" | "i386" | "i486" | "i586" | "i686" => Some(Arch::X86),
+        "aarch64" | "arm64" => Some(Arch::Aarch64),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct JavaProps {
+    pub version: Option<String>,
+    pub arch: Option<String>,
+    pub vendor: Option<String>,
+}
+
+/// Fichier `release` d'un JDK/JRE : `JAVA_VERSION="17.0.8"`, `OS_ARCH="amd64"`, `IMPLEMENTOR=...`.
+pub fn parse_release_file(text: &str) -> JavaProps {
+    let mut props = JavaProps::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim() {
+            "JAVA_VERSION" => props.version = Some(value),
+            "OS_ARCH" => props.arch = Some(value),
+            "IMPLEMENTOR" => props.vendor = Some(value),
+            _ => {}
+        }
+    }
+    props
+}
+
+/// Sortie (stderr) de `java -XshowSettings:properties -version` : lignes `    clé = valeur`.
+pub fn parse_show_settings(text: &str) -> JavaProps {
+    let mut props = JavaProps::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "java.version" => props.version = Some(value),
+            "os.arch" => props.arch = Some(value),
+            "java.vendor" => props.vendor = Some(value),
+            _ => {}
+        }
+    }
+    props
+}
+
+/// Java 8 exigé tel quel (LWJGL 2) ; au-delà, une version plus récente convient.
+pub fn is_compatible(major: u32, arch: Option<Arch>, required_major: u32, platform: &Platform) -> bool {
+    let version_ok = if required_major <= 8 {
+        major == 8
+    } else {
+        major >= required_major
+    };
+    version_ok && arch == Some(platform.arch)
+}
+
+/* ===================== Emplacements connus ===================== */
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+        .unwrap_or_default()
+}
+
+/// Dossiers « home » candidats (contenant `bin/java`).
+fn candidate_homes(platform: &Platform) -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        homes.push(PathBuf::from(home));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            let java = dir.join(JAVA_EXE);
+            // `/usr/bin/java` est souvent un lien vers `/usr/lib/jvm/.../bin/java`.
+            if let Ok(real) = std::fs::canonicalize(&java) {
+                if let Some(home) = real.parent().and_then(Path::parent) {
+                    homes.push(home.to_path_buf());
+                }
+            }
+        }
+    }
+
+    let user_home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+    if let Some(h) = &user_home {
+        homes.extend(subdirs(&h.join(".sdkman/candidates/java")));
+        homes.extend(subdirs(&h.join(".jdks")));
+    }
+    if let Some(sdkman) = std::env::var_os("SDKMAN_DIR") {
+        homes.extend(subdirs(&PathBuf::from(sdkman).join("candidates/java")));
+    }
+
+    match platform.os {
+        OsName::Linux => {
+            for root in ["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java", "/opt"] {
+                homes.extend(subdirs(Path::new(root)));
+            }
+        }
+        OsName::Osx => {
+            for root in subdirs(Path::new("/Library/Java/JavaVirtualMachines")) {
+                homes.push(root.join("Contents/Home"));
+            }
+        }
+        OsName::Windows => {
+            // Équivalent des clés de registre JavaSoft / vendeurs : leurs dossiers d'install.
+            let vendors = [
+                "Java",
+                "Eclipse Adoptium",
+                "Eclipse Foundation",
+                "AdoptOpenJDK",
+                "Microsoft",
+                "Zulu",
+                "BellSoft",
+                "Amazon Corretto",
+                "Semeru",
+            ];
+            for var in ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"] {
+                let Some(pf) = std::env::var_os(var) else {
+                    continue;
+                };
+                for vendor in vendors {
+                    homes.extend(subdirs(&PathBuf::from(&pf).join(vendor)));
+                }
+            }
+        }
+    }
+    homes
+}
+
+/* ===================== Sonde ===================== */
+
+fn probe(java: &Path, home: &Path) -> Option<JavaProps> {
+    let from_release = std::fs::read_to_string(home.join("release"))
+        .map(|t| parse_release_file(&t))
+        .unwrap_or_default();
+    if from_release.version.is_some() && from_release.arch.is_some() {
+        return Some(from_release);
+    }
+    let mut cmd = Command::new(java);
+    cmd.args(["-XshowSettings:properties", "-version"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let out = cmd.output().ok()?;
+    let props = parse_show_settings(&String::from_utf8_lossy(&out.stderr));
+    props.version.is_some().then_some(props)
+}
+
+fn detect(java_bin: &Path, home: &Path, managed: bool, required_major: u32, platform: &Platform) -> Option<DetectedJdk> {
+    let props = probe(java_bin, home)?;
+    let version = props.version?;
+    let major = parse_major(&version)?;
+    let arch = props.arch.unwrap_or_default();
+    Some(DetectedJdk {
+        path: java_bin.display().to_string(),
+        home: home.display().to_string(),
+        compatible: is_compatible(major, parse_arch(&arch), required_major, platform),
+        version,
+        major,
+        arch,
+        vendor: props.vendor,
+        managed,
+    })
+}
+
+/// Runtimes gérés + JDK installés sur la machine, dédupliqués par exécutable réel.
+pub fn discover(dirs: &GameDir, platform: &Platform, required_major: u32) -> Vec<DetectedJdk> {
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+
+    let managed = subdirs(&dirs.root().join("runtime"))
+        .into_iter()
+        .map(|dir| (runtime::java_binary(&dir, platform), true));
+    let system = candidate_homes(platform)
+        .into_iter()
+        .map(|home| (home.join("bin").join(JAVA_EXE), false));
+
+    for (java, is_managed) in managed.chain(system) {
+        let Ok(real) = std::fs::canonicalize(&java) else {
+            continue;
+        };
+        if !seen.insert(real) {
+            continue;
+        }
+        // `<home>/bin/java` ; pour un runtime Mojang macOS, home = jre.bundle/Contents/Home.
+        let Some(home) = java.parent().and_then(Path::parent) else {
+            continue;
+        };
+        if let Some(jdk) = detect(&java, home, is_managed, required_major, platform) {
+            found.push(jdk);
+        }
+    }
+    found.sort_by(|a, b| b.compatible.cmp(&a.compatible).then(b.major.cmp(&a.major)));
+    found
+}
+
+/* ===================== Commande ===================== */
+
+/// Liste les JDK détectés ; `compatible` est calculé pour `version_id` (dernière release sinon).
+/// Le JSON d'une version installée est lu sur disque ; le manifeste n'est contacté qu'à défaut.
+#[tauri::command]
+pub async fn mc_list_jdks(app: tauri::AppHandle, version_id: Option<String>) -> Result<Vec<DetectedJdk>, String> {
+    let dirs = GameDir::from_app(&app).map_err(|e| e.to_string())?;
+    let version = match version_id.as_deref().and_then(|id| install::installed_version(&dirs, id)) {
+        Some(version) => version,
+        None => {
+            let dl = Downloader::new().map_err(|e| e.to_string())?;
+            install::resolve_version(&dl, &dirs, version_id.as_deref())
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    };
+    let required = runtime::required_java(&version).major_version;
+
+    tauri::async_runtime::spawn_blocking(move || discover(&dirs, &Platform::current(), required))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,12 +1,93 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+
+use super::args::{self, LaunchOptions};
+use super::auth;
+use super::download::{CancelToken, Downloader, ProgressFn};
+use super::install::{self, GameDir};
+use super::natives::{extract_natives, NativesDir};
+use super::rules::Platform;
+use super::runtime;
+use super::McError;
 
 #[derive(Debug, Deserialize)]
 pub struct LaunchArgs {
     pub server_id: String,
     pub ram_mb: u32,
+    /// Version vanilla à installer ; dernière release du manifeste si absente.
+    #[serde(default, alias = "versionId")]
+    pub version_id: Option<String>,
+}
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/* ===================== Registre des lancements ===================== */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LaunchPhase {
+    /// Installation / runtime / natives : annulable.
+    Preparing,
+    /// La JVM tourne : seul `mc_kill_game` l'arrête.
+    Running,
+}
+
+struct LaunchHandle {
+    cancel: CancelToken,
+    phase: LaunchPhase,
+}
+
+/// Lancements en cours, indexés par l'identifiant renvoyé par `mc_launch_server`.
+#[derive(Default)]
+pub struct LaunchRegistry {
+    launches: Mutex<HashMap<String, LaunchHandle>>,
+}
+
+impl LaunchRegistry {
+    fn register(&self) -> (String, CancelToken) {
+        let id = format!("{:016x}", rand::random::<u64>());
+        let cancel = CancelToken::default();
+        let handle = LaunchHandle {
+            cancel: cancel.clone(),
+            phase: LaunchPhase::Preparing,
+        };
+        self.launches.lock().unwrap().insert(id.clone(), handle);
+        (id, cancel)
+    }
+
+    fn set_phase(&self, id: &str, phase: LaunchPhase) {
+        if let Some(handle) = self.launches.lock().unwrap().get_mut(id) {
+            handle.phase = phase;
+        }
+    }
+
+    fn remove(&self, id: &str) {
+        self.launches.lock().unwrap().remove(id);
+    }
+
+    fn cancel(&self, id: &str, running_allowed: bool) -> Result<(), String> {
+        let launches = self.launches.lock().unwrap();
+        let handle = launches
+            .get(id)
+            .ok_or_else(|| format!("aucun lancement en cours avec l'id {id}"))?;
+        if handle.phase == LaunchPhase::Running && !running_allowed {
+            return Err("le jeu est déjà lancé : utilisez mc_kill_game pour l'arrêter".into());
+        }
+        handle.cancel.cancel();
+        Ok(())
+    }
 }
 
+/* ===================== Événements ===================== */
+
 #[derive(Debug, Serialize, Clone)]
 struct ProgressPayload {
     step: String,
@@ -14,7 +95,7 @@ struct ProgressPayload {
     detail: Option<String>,
 }
 
-fn emit_progress(app: &AppHandle, step: &str, percent: u8, detail: Option<&str>) {
+pub(super) fn emit_progress(app: &AppHandle, step: &str, percent: u8, detail: Option<&str>) {
     let payload = ProgressPayload {
         step: step.to_string(),
         percent,
@@ -22,52 +103,258 @@ fn emit_progress(app: &AppHandle, step: &str, percent: u8, detail: Option<&str>)
     };
     let _ = app.emit("mc://progress", payload);
 }
+/// Progression octets → pourcentage, projetée sur `[from, to]` de la barre.
+pub(super) fn progress_range(app: &AppHandle, step: &'static str, from: u8, to: u8) -> ProgressFn {
+    let app = app.clone();
+    let last_percent = AtomicU8::new(0);
+    Arc::new(move |done, total| {
+        let ratio = if total == 0 { 1.0 } else { done as f64 / total as f64 };
+        let percent = from + (ratio.min(1.0) * f64::from(to - from)) as u8;
+        if last_percent.swap(percent, Ordering::Relaxed) != percent {
+            let detail = format!(
+                "{:.1} / {:.1} Mo",
+                done as f64 / 1_048_576.0,
+                total as f64 / 1_048_576.0
+            );
+            emit_progress(&app, step, percent, Some(&detail));
+        }
+    })
+}
 fn emit_log(app: &AppHandle, line: &str) {
     let _ = app.emit("mc://log", serde_json::json!({ "line": line }));
 }
-fn emit_done(app: &AppHandle, ok: bool, error: Option<&str>) {
-    let _ = app.emit("mc://done", serde_json::json!({ "ok": ok, "error": error }));
+#[derive(Debug, Serialize, Clone, Default)]
+struct DonePayload {
+    launch_id: String,
+    ok: bool,
+    /// Arrêt demandé par `mc_cancel_launch` / `mc_kill_game` (ni succès ni plantage).
+    cancelled: bool,
+    error: Option<String>,
+    exit_code: Option<i32>,
+}
+
+fn emit_done(app: &AppHandle, payload: DonePayload) {
+    let _ = app.emit("mc://done", payload);
+}
+
+/* ===================== Processus du jeu ===================== */
+
+/// Relaie chaque ligne (UTF-8 approximatif) d'un flux du jeu vers `mc://log`.
+async fn pipe_lines<R: AsyncRead + Unpin>(app: AppHandle, stream: R) {
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                emit_log(&app, line.trim_end_matches(['\r', '\n']));
+            }
+        }
+    }
+}
+
+/// Tue la JVM et ses processus enfants.
+async fn kill_tree(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        #[cfg(windows)]
+        {
+            let mut cmd = Command::new("taskkill");
+            cmd.args(["/PID", &pid.to_string(), "/T", "/F"])
+                .creation_flags(CREATE_NO_WINDOW);
+            let _ = cmd.status().await;
+        }
+        // La JVM est chef de son propre groupe (cf. `run_game`) : on tue tout le groupe.
+        #[cfg(unix)]
+        {
+            let _ = Command::new("kill")
+                .args(["-KILL", "--", &format!("-{pid}")])
+                .status()
+                .await;
+        }
+    }
+    let _ = child.kill().await;
+}
+
+/// Lance la JVM, relaie stdout/stderr et attend la fin du jeu (ou l'arrêt demandé).
+async fn run_game(
+    app: &AppHandle,
+    java_bin: &Path,
+    argv: &[String],
+    game_dir: &Path,
+    cancel: &CancelToken,
+) -> Result<ExitStatus, McError> {
+    let mut cmd = Command::new(java_bin);
+    cmd.args(argv)
+        .current_dir(game_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| McError::Io(format!("{}: {e}", java_bin.display())))?;
+
+    emit_progress(app, "en jeu", 100, None);
+    let pipes: Vec<_> = [
+        child.stdout.take().map(|s| tauri::async_runtime::spawn(pipe_lines(app.clone(), s))),
+        child.stderr.take().map(|s| tauri::async_runtime::spawn(pipe_lines(app.clone(), s))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let waited = tokio::select! {
+        status = child.wait() => Some(status),
+        _ = cancel.cancelled() => None,
+    };
+    let result = match waited {
+        Some(status) => status.map_err(|e| McError::Io(e.to_string())),
+        None => {
+            kill_tree(&mut child).await;
+            Err(McError::Cancelled)
+        }
+    };
+    // Vide ce qui reste dans les tubes avant d'annoncer la fin.
+    for pipe in pipes {
+        let _ = pipe.await;
+    }
+    result
+}
+
+/// Message d'un arrêt anormal : code de sortie, ou signal sous Unix.
+fn crash_message(status: &ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("le jeu s'est arrêté avec le code {code}"),
+        None => "le jeu a été interrompu par le système".to_string(),
+    }
+}
+
+/// Installation, runtime Java, extraction des natives, puis exécution du jeu jusqu'à sa fermeture.
+async fn run_launch(
+    app: &AppHandle,
+    launch_id: &str,
+    cancel: &CancelToken,
+    version_id: Option<&str>,
+    ram_mb: u32,
+) -> Result<ExitStatus, McError> {
+    let session = auth::launch_session(app).await.map_err(|e| McError::Auth(e.to_string()))?;
+    emit_log(app, &format!("Compte : {}", session.player_name));
+    cancel.check()?;
+
+    let dl = Downloader::new()?.with_cancel(cancel.clone());
+    let installed = install::install_version(app, &dl, version_id).await?;
+    let version = installed.version;
+    emit_log(app, &format!("Version {} installée et vérifiée.", version.id));
+
+    let dirs = GameDir::from_app(app)?;
+    let platform = Platform::current();
+
+    let java = runtime::required_java(&version);
+    emit_progress(app, "java", 88, Some(&java.component));
+    let java_bin = runtime::ensure_runtime(
+        &dl,
+        &dirs,
+        &platform,
+        &java,
+        progress_range(app, "java", 88, 96),
+    )
+    .await?;
+    emit_log(app, &format!("Java {} ({}) : {}", java.major_version, java.component, java_bin.display()));
+
+    // Dossier de natives propre à ce lancement, supprimé quand `natives` est libéré.
+    emit_progress(app, "natives", 97, Some("extraction"));
+    let natives = NativesDir::create(&dirs, &version.id)?;
+    {
+        let (dirs, version, platform) = (dirs.clone(), version.clone(), platform.clone());
+        let dest = natives.path().to_path_buf();
+        tokio::task::spawn_blocking(move || extract_natives(&dirs, &version, &platform, &dest))
+            .await
+            .map_err(|e| McError::Io(e.to_string()))??;
+    }
+    cancel.check()?;
+
+    let opts = LaunchOptions {
+        natives_dir: natives.path().to_path_buf(),
+        asset_layout: installed.asset_layout,
+        session: Some(session),
+        ram_mb,
+        platform,
+        demo: false,
+        resolution: None,
+        quick_play_server: None,
+    };
+    let argv = args::build_argv(&dirs, &version, &opts);
+    let command = args::display_command(&java_bin.display().to_string(), &argv, opts.session.as_ref());
+    emit_log(app, &format!("Commande : {command}"));
+
+    app.state::<LaunchRegistry>().set_phase(launch_id, LaunchPhase::Running);
+    let status = run_game(app, &java_bin, &argv, dirs.root(), cancel).await;
+    // Les natives restent en place tant que la JVM tourne.
+    drop(natives);
+    status
 }
 
+/// Démarre un lancement en tâche de fond et renvoie son identifiant.
 #[tauri::command]
-pub async fn mc_launch_server(app: tauri::AppHandle, args: LaunchArgs) -> Result<(), String> {
-    // Simule les étapes (remplace par ton vrai pipeline)
+pub async fn mc_launch_server(
+    app: tauri::AppHandle,
+    registry: State<'_, LaunchRegistry>,
+    args: LaunchArgs,
+) -> Result<String, String> {
     let server = args.server_id;
     let ram = args.ram_mb;
+    let version_id = args.version_id;
+    let (launch_id, cancel) = registry.register();
 
     tauri::async_runtime::spawn({
         let app = app.clone();
+        let launch_id = launch_id.clone();
         async move {
             emit_log(&app, &format!("Préparation du lancement… serveur={server}, RAM={} Mo", ram));
 
-            // Étape 1: vérification
-            emit_progress(&app, "vérification", 5, Some("lecture config"));
-            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
-
-            // Étape 2: résolution (manifests, librairies)
-            emit_progress(&app, "résolution", 15, Some("manifestes"));
-            tokio::time::sleep(std::time::Duration::from_millis(400)).await;
-
-            // Étape 3: téléchargement (boucle)
-            for p in [20,30,45,60,75,85].iter() {
-                emit_progress(&app, "téléchargement", *p as u8, Some("libs/assets"));
-                tokio::time::sleep(std::time::Duration::from_millis(350)).await;
-            }
-
-            // Étape 4: vérification des fichiers
-            emit_progress(&app, "vérification", 90, Some("intégrité"));
-            tokio::time::sleep(std::time::Duration::from_millis(350)).await;
+            let result = run_launch(&app, &launch_id, &cancel, version_id.as_deref(), ram).await;
+            app.state::<LaunchRegistry>().remove(&launch_id);
 
-            // Étape 5: lancement
-            emit_log(&app, "Démarrage de la JVM…");
-            emit_progress(&app, "lancement", 98, Some("java args"));
-            tokio::time::sleep(std::time::Duration::from_millis(400)).await;
-
-            // Fin
-            emit_progress(&app, "prêt", 100, None);
-            emit_done(&app, true, None);
+            let done = match result {
+                Ok(status) if status.success() => {
+                    emit_log(&app, "Jeu fermé.");
+                    DonePayload { ok: true, exit_code: status.code(), ..Default::default() }
+                }
+                Ok(status) => {
+                    let msg = crash_message(&status);
+                    emit_log(&app, &format!("Plantage : {msg}"));
+                    DonePayload { error: Some(msg), exit_code: status.code(), ..Default::default() }
+                }
+                Err(McError::Cancelled) => {
+                    emit_log(&app, "Lancement arrêté.");
+                    DonePayload { cancelled: true, ..Default::default() }
+                }
+                Err(e) => {
+                    let msg = e.to_string();
+                    emit_log(&app, &format!("Échec du lancement : {msg}"));
+                    DonePayload { error: Some(msg), ..Default::default() }
+                }
+            };
+            emit_done(&app, DonePayload { launch_id, ..done });
         }
     });
 
-    Ok(())
+    Ok(launch_id)
+}
+
+/// Annule l'installation / préparation en cours ; les `.part` entamés sont supprimés.
+#[tauri::command]
+pub fn mc_cancel_launch(registry: State<'_, LaunchRegistry>, launch_id: String) -> Result<(), String> {
+    registry.cancel(&launch_id, false)
+}
+
+/// Arrête le lancement quelle que soit son étape, y compris la JVM et ses enfants.
+#[tauri::command]
+pub fn mc_kill_game(registry: State<'_, LaunchRegistry>, launch_id: String) -> Result<(), String> {
+    registry.cancel(&launch_id, true)
 }
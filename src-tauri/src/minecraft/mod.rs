@@ -1,11 +1,28 @@
+pub mod accounts;
+pub mod args;
+pub mod assets;
 pub mod auth;
+pub mod auth_config;
+pub mod auth_error;
+pub mod credentials;
+pub mod download;
+pub mod install;
+pub mod jdk;
 pub mod launch;
+pub mod natives;
+pub mod oauth;
+pub mod profile;
+pub mod rules;
+pub mod runtime;
+pub mod skin_library;
+pub mod skin_render;
+pub mod version;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use reqwest;
 
-const VERSION_MANIFEST_URL: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
+const VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 
 #[derive(Debug, Error)]
 pub enum McError {
@@ -13,13 +30,31 @@ pub enum McError {
     Http(String),
     #[error("json error: {0}")]
     Json(String),
+    #[error("io error: {0}")]
+    Io(String),
+    #[error("integrity error: {0}")]
+    Integrity(String),
+    #[error("unknown version: {0}")]
+    UnknownVersion(String),
+    #[error("java runtime error: {0}")]
+    Runtime(String),
+    #[error("authentication error: {0}")]
+    Auth(String),
+    #[error("lancement annulé")]
+    Cancelled,
 }
 
 #[derive(Debug, Deserialize)]
 struct ManifestRoot {
+    latest: LatestVersions,
     versions: Vec<VersionEntry>,
 }
 
+#[derive(Debug, Deserialize)]
+struct LatestVersions {
+    release: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct VersionEntry {
@@ -27,6 +62,8 @@ struct VersionEntry {
     #[serde(rename = "type")]
     kind: String,
     release_time: String,
+    url: String,
+    sha1: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -58,12 +95,31 @@ pub fn mc_fetch_versions() -> Result<Vec<McVersion>, String> {
     Ok(out)
 }
 
+/// Aperçu de la commande de lancement construite depuis le JSON de version réel.
 #[tauri::command]
-pub fn mc_prepare_vanilla(version_id: String, ram_mb: u32) -> Result<String, String> {
-    let cmd = format!(
-        "java -Xmx{ram}m -XX:+UseG1GC -jar ./minecraft/{vid}/client.jar --username Player --version {vid}",
-        ram = ram_mb,
-        vid = version_id
-    );
-    Ok(cmd)
+pub async fn mc_prepare_vanilla(app: tauri::AppHandle, version_id: String, ram_mb: u32) -> Result<String, String> {
+    let dl = download::Downloader::new().map_err(|e| e.to_string())?;
+    let dirs = install::GameDir::from_app(&app).map_err(|e| e.to_string())?;
+    let version = install::resolve_version(&dl, &dirs, Some(&version_id))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let index = assets::fetch_asset_index(&dl, &dirs, &version)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let opts = args::LaunchOptions {
+        natives_dir: dirs.version_dir(&version.id).join("natives"),
+        asset_layout: index.layout(),
+        session: None,
+        ram_mb,
+        platform: rules::Platform::current(),
+        demo: false,
+        resolution: None,
+        quick_play_server: None,
+    };
+    let java = runtime::required_java(&version);
+    let java_bin = runtime::java_binary(&runtime::runtime_dir(&dirs, &java.component), &opts.platform);
+    let argv = args::build_argv(&dirs, &version, &opts);
+    Ok(args::display_command(&java_bin.display().to_string(), &argv, None))
 }
@@ -0,0 +1,100 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use super::install::GameDir;
+use super::rules::{library_applies, Platform};
+use super::version::{Artifact, Library, VersionJson};
+use super::McError;
+
+/* ===================== Classifiers (≤ 1.18) ===================== */
+
+/// Classifier de natives pour la plateforme, `${arch}` remplacé (`natives-windows-64`).
+pub fn native_classifier(lib: &Library, platform: &Platform) -> Option<String> {
+    let raw = lib.natives.as_ref()?.get(platform.os.as_str())?;
+    Some(raw.replace("${arch}", platform.arch.bits()))
+}
+
+/// Jar de natives à télécharger/extraire pour cette librairie, s'il y en a un.
+pub fn native_artifact<'a>(lib: &'a Library, platform: &Platform) -> Option<&'a Artifact> {
+    let classifier = native_classifier(lib, platform)?;
+    lib.downloads.as_ref()?.classifiers.as_ref()?.get(&classifier)
+}
+
+/* ===================== Dossier de natives par lancement ===================== */
+
+/// Dossier temporaire propre à un lancement, supprimé au `drop` (fin du jeu).
+#[derive(Debug)]
+pub struct NativesDir {
+    path: PathBuf,
+}
+
+impl NativesDir {
+    pub fn create(dirs: &GameDir, version_id: &str) -> Result<Self, McError> {
+        let nonce: u32 = rand::random();
+        let path = dirs
+            .root()
+            .join("natives")
+            .join(format!("{version_id}-{nonce:08x}"));
+        std::fs::create_dir_all(&path).map_err(|e| McError::Io(format!("{}: {e}", path.display())))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for NativesDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/* ===================== Extraction ===================== */
+
+fn excluded(name: &str, exclude: &[String]) -> bool {
+    exclude.iter().any(|prefix| name.starts_with(prefix.as_str()))
+}
+
+fn extract_jar(jar: &Path, dest: &Path, exclude: &[String]) -> Result<(), McError> {
+    let zip_err = |e: zip::result::ZipError| McError::Io(format!("{}: {e}", jar.display()));
+    let io_err = |p: &Path, e: std::io::Error| McError::Io(format!("{}: {e}", p.display()));
+
+    let file = File::open(jar).map_err(|e| io_err(jar, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_err)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_err)?;
+        if entry.is_dir() || excluded(entry.name(), exclude) {
+            continue;
+        }
+        // Refuse les chemins qui sortiraient du dossier (`../`).
+        let Some(rel) = entry.enclosed_name() else {
+            continue;
+        };
+        let out = dest.join(rel);
+        if let Some(dir) = out.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| io_err(dir, e))?;
+        }
+        let mut target = File::create(&out).map_err(|e| io_err(&out, e))?;
+        std::io::copy(&mut entry, &mut target).map_err(|e| io_err(&out, e))?;
+    }
+    Ok(())
+}
+
+/// Décompresse les jars de natives de la version dans `dest`, en respectant `extract.exclude`.
+pub fn extract_natives(
+    dirs: &GameDir,
+    version: &VersionJson,
+    platform: &Platform,
+    dest: &Path,
+) -> Result<(), McError> {
+    for lib in version.libraries.iter().filter(|l| library_applies(l, platform)) {
+        let Some(artifact) = native_artifact(lib, platform) else {
+            continue;
+        };
+        let exclude = lib.extract.as_ref().map(|e| e.exclude.as_slice()).unwrap_or(&[]);
+        extract_jar(&dirs.libraries().join(&artifact.path), dest, exclude)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use super::auth_error::AuthError;
+
+/* ===================== PKCE (RFC 7636, S256) ===================== */
+
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Result<Self, AuthError> {
+        let mut buf = [0u8; 32];
+        getrandom::fill(&mut buf).map_err(|e| AuthError::Service(format!("pkce: {e}")))?;
+        Ok(Self::from_verifier(general_purpose::URL_SAFE_NO_PAD.encode(buf)))
+    }
+
+    pub fn from_verifier(verifier: String) -> Self {
+        let challenge = general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self { verifier, challenge }
+    }
+}
+
+/// Valeur `state` anti-CSRF, renvoyée telle quelle par le serveur d'autorisation.
+pub fn random_state() -> String {
+    format!("{:016x}{:016x}", rand::random::<u64>(), rand::random::<u64>())
+}
+
+pub fn authorize_url(
+    authorize_endpoint: &str,
+    client_id: &str,
+    scopes: &str,
+    redirect_uri: &str,
+    pkce: &Pkce,
+    state: &str,
+) -> Result<String, AuthError> {
+    let url = reqwest::Url::parse_with_params(
+        authorize_endpoint,
+        &[
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("redirect_uri", redirect_uri),
+            ("response_mode", "query"),
+            ("scope", scopes),
+            ("code_challenge", pkce.challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("state", state),
+            // plusieurs comptes possibles : ne pas réutiliser la session du navigateur en silence
+            ("prompt", "select_account"),
+        ],
+    )
+    .map_err(|e| AuthError::Service(format!("authorize url: {e}")))?;
+    Ok(url.into())
+}
+
+/* ===================== Redirection loopback (127.0.0.1, un seul code) ===================== */
+
+const PAGE_OK: &str = "<!doctype html><meta charset=\"utf-8\"><title>Kashir Launcher</title>\
+<p>Connexion réussie. Vous pouvez fermer cet onglet et revenir au launcher.</p>";
+const PAGE_ERR: &str = "<!doctype html><meta charset=\"utf-8\"><title>Kashir Launcher</title>\
+<p>La connexion a échoué. Revenez au launcher pour réessayer.</p>";
+
+/// Délai laissé à une connexion pour envoyer sa ligne de requête : les navigateurs ouvrent des
+/// sockets spéculatives qui peuvent rester muettes.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Loopback {
+    listener: TcpListener,
+    pub redirect_uri: String,
+}
+
+impl Loopback {
+    /// Écoute sur un port libre de 127.0.0.1 (Entra ID accepte n'importe quel port en loopback).
+    pub async fn bind() -> Result<Self, AuthError> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| AuthError::Network(format!("loopback: {e}")))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| AuthError::Network(format!("loopback: {e}")))?
+            .port();
+        Ok(Self {
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{port}/"),
+        })
+    }
+
+    /// Attend la redirection portant `code` (ou `error`) avec le bon `state`, puis ferme l'écoute.
+    /// Les autres requêtes (favicon, state inconnu) sont ignorées. Chaque connexion est lue dans
+    /// sa propre tâche : une socket muette ne bloque pas la redirection qui suit.
+    pub async fn wait_for_code(self, state: &str) -> Result<String, AuthError> {
+        let (tx, mut rx) = mpsc::channel::<(TcpStream, String)>(8);
+        loop {
+            let (mut stream, target) = tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (mut stream, _) = accepted.map_err(|e| AuthError::Network(format!("loopback: {e}")))?;
+                    let tx = tx.clone();
+                    tokio::spawn(async move {
+                        let read = tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request_target(&mut stream)).await;
+                        if let Ok(Some(target)) = read {
+                            let _ = tx.send((stream, target)).await;
+                        }
+                    });
+                    continue;
+                }
+                Some(request) = rx.recv() => request,
+            };
+            let Ok(url) = reqwest::Url::parse(&format!("http://127.0.0.1{target}")) else {
+                respond(&mut stream, "400 Bad Request", "").await;
+                continue;
+            };
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            if !params.contains_key("code") && !params.contains_key("error") {
+                respond(&mut stream, "404 Not Found", "").await;
+                continue;
+            }
+            if params.get("state").map(String::as_str) != Some(state) {
+                respond(&mut stream, "400 Bad Request", PAGE_ERR).await;
+                continue;
+            }
+
+            if let Some(code) = params.get("code") {
+                respond(&mut stream, "200 OK", PAGE_OK).await;
+                return Ok(code.clone());
+            }
+            respond(&mut stream, "200 OK", PAGE_ERR).await;
+            let error = params.get("error").map(String::as_str).unwrap_or_default();
+            if error == "access_denied" {
+                return Err(AuthError::Declined);
+            }
+            let desc = params.get("error_description").map(String::as_str).unwrap_or_default();
+            return Err(AuthError::Service(format!("authorization failed: {error} – {desc}")));
+        }
+    }
+}
+
+/// Cible de la ligne `GET <cible> HTTP/1.1` ; les en-têtes sont lus pour ne pas couper la
+/// connexion avant la réponse.
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await.ok()?;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {}
+        }
+    }
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) if target.starts_with('/') => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let resp = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(resp.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/* ===================== Échange du code ===================== */
+
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: u64,
+}
+
+pub async fn exchange_code(
+    client: &reqwest::Client,
+    token_url: &str,
+    client_id: &str,
+    scopes: &str,
+    code: &str,
+    redirect_uri: &str,
+    pkce: &Pkce,
+) -> Result<TokenResponse, AuthError> {
+    let form = [
+        ("grant_type", "authorization_code"),
+        ("client_id", client_id),
+        ("scope", scopes),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("code_verifier", pkce.verifier.as_str()),
+    ];
+    let res = client
+        .post(token_url)
+        .form(&form)
+        .send()
+        .await?;
+
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+        if text.contains("\"invalid_grant\"") {
+            return Err(AuthError::InvalidGrant);
+        }
+        return Err(AuthError::Service(format!("token exchange failed: {status} – {text}")));
+    }
+    serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))
+}
@@ -0,0 +1,309 @@
+use std::io::Cursor;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::auth::{self, AuthContext, McCape, McProfileLite};
+use super::auth_error::AuthError;
+
+/* ===================== Skins (Minecraft Services) ===================== */
+
+/// Modèle de bras : `classic` (Steve, 4 px) ou `slim` (Alex, 3 px). Mojang l'écrit en majuscules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkinVariant {
+    #[serde(alias = "CLASSIC")]
+    Classic,
+    #[serde(alias = "SLIM")]
+    Slim,
+}
+
+impl SkinVariant {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Slim => "slim",
+        }
+    }
+}
+
+/// Vérifie qu'un skin est un PNG lisible de 64x64 (format 1.8+) ou 64x32 (ancien format).
+/// Renvoie ses dimensions.
+pub fn validate_skin_png(data: &[u8]) -> Result<(u32, u32), AuthError> {
+    let invalid = |e: png::DecodingError| AuthError::InvalidSkin(format!("png illisible: {e}"));
+    let mut reader = png::Decoder::new(Cursor::new(data)).read_info().map_err(invalid)?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if width != 64 || (height != 64 && height != 32) {
+        return Err(AuthError::InvalidSkin(format!("{width}x{height}, attendu 64x64 ou 64x32")));
+    }
+    // Décodage complet : un fichier tronqué est refusé ici plutôt que par Mojang.
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| AuthError::InvalidSkin("png trop grand".into()))?;
+    let mut buf = vec![0; size];
+    reader.next_frame(&mut buf).map_err(invalid)?;
+    Ok((width, height))
+}
+
+/// Lit la réponse (le profil mis à jour) et la recopie dans le cache et la liste des comptes.
+async fn updated_profile(ctx: &AuthContext, res: reqwest::Response, what: &str) -> Result<McProfileLite, AuthError> {
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    let profile = auth::profile_response(status, &text, what)?;
+    auth::store_profile(ctx, &profile).await?;
+    Ok(profile)
+}
+
+/// Envoie un skin PNG (octets du fichier) pour le compte actif.
+#[tauri::command]
+pub async fn mc_skin_upload(app: AppHandle, png: Vec<u8>, variant: SkinVariant) -> Result<McProfileLite, AuthError> {
+    upload_skin(&AuthContext::from_app(&app)?, png, variant).await
+}
+
+pub async fn upload_skin(ctx: &AuthContext, png: Vec<u8>, variant: SkinVariant) -> Result<McProfileLite, AuthError> {
+    validate_skin_png(&png)?;
+    let token = auth::active_mc_token(ctx).await?;
+    let file = reqwest::multipart::Part::bytes(png)
+        .file_name("skin.png")
+        .mime_str("image/png")?;
+    let form = reqwest::multipart::Form::new()
+        .text("variant", variant.as_str())
+        .part("file", file);
+    let res = ctx
+        .http
+        .post(ctx.config.skins_url())
+        .bearer_auth(&token)
+        .multipart(form)
+        .send()
+        .await?;
+    updated_profile(ctx, res, "skin upload").await
+}
+
+/// Applique un skin déjà hébergé (textures.minecraft.net ou autre URL publique).
+#[tauri::command]
+pub async fn mc_skin_set_url(app: AppHandle, url: String, variant: SkinVariant) -> Result<McProfileLite, AuthError> {
+    set_skin_url(&AuthContext::from_app(&app)?, &url, variant).await
+}
+
+pub async fn set_skin_url(ctx: &AuthContext, url: &str, variant: SkinVariant) -> Result<McProfileLite, AuthError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| AuthError::InvalidSkin(format!("url {url}: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AuthError::InvalidSkin(format!("url {url}: http(s) attendu")));
+    }
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .post(ctx.config.skins_url())
+        .bearer_auth(&token)
+        .json(&json!({ "variant": variant.as_str(), "url": url }))
+        .send()
+        .await?;
+    updated_profile(ctx, res, "skin url").await
+}
+
+/// Revient au skin par défaut attribué par Mojang.
+#[tauri::command]
+pub async fn mc_skin_reset(app: AppHandle) -> Result<McProfileLite, AuthError> {
+    reset_skin(&AuthContext::from_app(&app)?).await
+}
+
+pub async fn reset_skin(ctx: &AuthContext) -> Result<McProfileLite, AuthError> {
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .delete(ctx.config.active_skin_url())
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    updated_profile(ctx, res, "skin reset").await
+}
+
+/* ===================== Capes ===================== */
+
+/// Capes possédées par le compte actif (profil relu avec le jeton en cache).
+#[tauri::command]
+pub async fn mc_capes_list(app: AppHandle) -> Result<Vec<McCape>, AuthError> {
+    list_capes(&AuthContext::from_app(&app)?).await
+}
+
+pub async fn list_capes(ctx: &AuthContext) -> Result<Vec<McCape>, AuthError> {
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .get(ctx.config.profile_url())
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    Ok(updated_profile(ctx, res, "profile").await?.capes)
+}
+
+/// Porte une cape possédée (`id` issu de `mc_capes_list`).
+#[tauri::command]
+pub async fn mc_cape_equip(app: AppHandle, cape_id: String) -> Result<McProfileLite, AuthError> {
+    equip_cape(&AuthContext::from_app(&app)?, &cape_id).await
+}
+
+pub async fn equip_cape(ctx: &AuthContext, cape_id: &str) -> Result<McProfileLite, AuthError> {
+    if !list_capes(ctx).await?.iter().any(|c| c.id == cape_id) {
+        return Err(AuthError::UnknownCape(cape_id.to_string()));
+    }
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .put(ctx.config.active_cape_url())
+        .bearer_auth(&token)
+        .json(&json!({ "capeId": cape_id }))
+        .send()
+        .await?;
+    updated_profile(ctx, res, "cape equip").await
+}
+
+/// Masque la cape portée (elle reste possédée).
+#[tauri::command]
+pub async fn mc_cape_hide(app: AppHandle) -> Result<McProfileLite, AuthError> {
+    hide_cape(&AuthContext::from_app(&app)?).await
+}
+
+pub async fn hide_cape(ctx: &AuthContext) -> Result<McProfileLite, AuthError> {
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .delete(ctx.config.active_cape_url())
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    updated_profile(ctx, res, "cape hide").await
+}
+
+/* ===================== Pseudo ===================== */
+
+/// Règles Mojang : 3 à 16 caractères parmi `[A-Za-z0-9_]`. Vérifié avant tout appel réseau.
+pub fn validate_profile_name(name: &str) -> Result<(), AuthError> {
+    let len = name.chars().count();
+    if !(3..=16).contains(&len) {
+        return Err(AuthError::InvalidName(format!("{name}: {len} caractères")));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_')) {
+        return Err(AuthError::InvalidName(format!("{name}: caractère {c:?} interdit")));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NameChangeRaw {
+    name_change_allowed: bool,
+    changed_at: Option<String>,
+    created_at: Option<String>,
+}
+
+/// Droit au changement de pseudo ; dates ISO 8601 telles que renvoyées par Mojang.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NameChangeInfo {
+    pub allowed: bool,
+    /// Dernier changement (absent si le pseudo n'a jamais changé).
+    pub changed_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameAvailability {
+    #[serde(alias = "AVAILABLE")]
+    Available,
+    #[serde(alias = "DUPLICATE")]
+    Duplicate,
+    #[serde(alias = "NOT_ALLOWED")]
+    NotAllowed,
+}
+
+#[derive(Deserialize)]
+struct NameAvailabilityRaw {
+    status: NameAvailability,
+}
+
+/// Corps d'erreur des pseudos : `{"details": {"status": "DUPLICATE" | "NOT_ALLOWED"}}`.
+#[derive(Deserialize)]
+struct NameErrorRaw {
+    details: NameAvailabilityRaw,
+}
+
+/// Refus portant sur le pseudo lui-même (pris ou filtré par Mojang), d'après `details.status`.
+pub(super) fn name_rejected(text: &str) -> bool {
+    serde_json::from_str::<NameErrorRaw>(text).is_ok_and(|e| e.details.status != NameAvailability::Available)
+}
+
+#[tauri::command]
+pub async fn mc_name_change_info(app: AppHandle) -> Result<NameChangeInfo, AuthError> {
+    name_change_info(&AuthContext::from_app(&app)?).await
+}
+
+pub async fn name_change_info(ctx: &AuthContext) -> Result<NameChangeInfo, AuthError> {
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .get(ctx.config.name_change_url())
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(AuthError::Service(format!("namechange failed: {status} – {text}")));
+    }
+    let raw: NameChangeRaw =
+        serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
+    Ok(NameChangeInfo { allowed: raw.name_change_allowed, changed_at: raw.changed_at, created_at: raw.created_at })
+}
+
+/// Utilisable aussi avant la création du profil (connexion en attente).
+#[tauri::command]
+pub async fn mc_name_available(app: AppHandle, name: String) -> Result<NameAvailability, AuthError> {
+    name_available(&AuthContext::from_app(&app)?, &name).await
+}
+
+pub async fn name_available(ctx: &AuthContext, name: &str) -> Result<NameAvailability, AuthError> {
+    validate_profile_name(name)?;
+    let token = auth::profile_setup_token(ctx).await?;
+    let res = ctx
+        .http
+        .get(ctx.config.name_available_url(name))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(AuthError::Service(format!("name availability failed: {status} – {text}")));
+    }
+    let raw: NameAvailabilityRaw =
+        serde_json::from_str(&text).map_err(|e| AuthError::Service(format!("json error: {e} – body: {text}")))?;
+    Ok(raw.status)
+}
+
+/// Renomme le profil du compte actif ; renvoie le profil mis à jour.
+#[tauri::command]
+pub async fn mc_name_change(app: AppHandle, name: String) -> Result<McProfileLite, AuthError> {
+    change_name(&AuthContext::from_app(&app)?, &name).await
+}
+
+pub async fn change_name(ctx: &AuthContext, name: &str) -> Result<McProfileLite, AuthError> {
+    validate_profile_name(name)?;
+    let token = auth::active_mc_token(ctx).await?;
+    let res = ctx
+        .http
+        .put(ctx.config.name_url(name))
+        .bearer_auth(&token)
+        .send()
+        .await?;
+    if res.status() == reqwest::StatusCode::FORBIDDEN {
+        // Pseudo refusé, sinon délai non écoulé.
+        let text = res.text().await.unwrap_or_default();
+        if name_rejected(&text) {
+            return Err(AuthError::NameUnavailable(name.to_string()));
+        }
+        return Err(AuthError::NameChangeNotAllowed);
+    }
+    updated_profile(ctx, res, "name change").await
+}
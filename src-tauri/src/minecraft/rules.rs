@@ -0,0 +1,193 @@
+use regex::Regex;
+
+use super::version::{Library, OsRule, Rule, RuleAction};
+
+/* ===================== Plateforme cible ===================== */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsName {
+    Windows,
+    Linux,
+    Osx,
+}
+
+impl OsName {
+    /// Nom utilisé dans `rules[].os.name` et dans les maps `natives`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OsName::Windows => "windows",
+            OsName::Linux => "linux",
+            OsName::Osx => "osx",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86,
+    X86_64,
+    Aarch64,
+}
+
+impl Arch {
+    /// Valeur de `${arch}` dans les classifiers de natives (`natives-windows-${arch}`).
+    pub fn bits(self) -> &'static str {
+        match self {
+            Arch::X86 => "32",
+            Arch::X86_64 | Arch::Aarch64 => "64",
+        }
+    }
+
+    fn matches(self, rule_arch: &str) -> bool {
+        match self {
+            Arch::X86 => rule_arch == "x86",
+            Arch::X86_64 => matches!(rule_arch, "x86_64" | "amd64"),
+            Arch::Aarch64 => matches!(rule_arch, "arm64" | "aarch64"),
+        }
+    }
+}
+
+/// Descripteur explicite de la plateforme : permet de calculer librairies et argv
+/// pour un autre OS/arch que celui de la machine courante.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub os: OsName,
+    pub arch: Arch,
+    /// Version de l'OS, comparée aux regex `rules[].os.version` (ex. `10.0`).
+    pub os_version: String,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        let os = if cfg!(windows) {
+            OsName::Windows
+        } else if cfg!(target_os = "macos") {
+            OsName::Osx
+        } else {
+            OsName::Linux
+        };
+        let arch = if cfg!(target_arch = "x86") {
+            Arch::X86
+        } else if cfg!(target_arch = "aarch64") {
+            Arch::Aarch64
+        } else {
+            Arch::X86_64
+        };
+        Self { os, arch, os_version: current_os_version() }
+    }
+
+    pub fn classpath_separator(&self) -> &'static str {
+        match self.os {
+            OsName::Windows => ";",
+            OsName::Linux | OsName::Osx => ":",
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_os_version() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn current_os_version() -> String {
+    // "Microsoft Windows [Version 10.0.19045.4170]" → "10.0.19045.4170"
+    std::process::Command::new("cmd")
+        .args(["/C", "ver"])
+        .output()
+        .ok()
+        .and_then(|o| {
+            let out = String::from_utf8_lossy(&o.stdout).to_string();
+            let start = out.find("Version ")? + "Version ".len();
+            let end = out[start..].find(']')? + start;
+            Some(out[start..end].trim().to_string())
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn current_os_version() -> String {
+    std::process::Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+fn current_os_version() -> String {
+    String::new()
+}
+
+/* ===================== Features ===================== */
+
+/// Features du launcher référencées par `rules[].features`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Features {
+    pub is_demo_user: bool,
+    pub has_custom_resolution: bool,
+    pub has_quick_plays_support: bool,
+    pub is_quick_play_singleplayer: bool,
+    pub is_quick_play_multiplayer: bool,
+    pub is_quick_play_realms: bool,
+}
+
+impl Features {
+    /// Feature inconnue = désactivée.
+    pub fn get(&self, name: &str) -> bool {
+        match name {
+            "is_demo_user" => self.is_demo_user,
+            "has_custom_resolution" => self.has_custom_resolution,
+            "has_quick_plays_support" => self.has_quick_plays_support,
+            "is_quick_play_singleplayer" => self.is_quick_play_singleplayer,
+            "is_quick_play_multiplayer" => self.is_quick_play_multiplayer,
+            "is_quick_play_realms" => self.is_quick_play_realms,
+            _ => false,
+        }
+    }
+}
+
+/* ===================== Évaluation ===================== */
+
+fn os_matches(os: &OsRule, platform: &Platform) -> bool {
+    if os.name.as_deref().is_some_and(|n| n != platform.os.as_str()) {
+        return false;
+    }
+    if os.arch.as_deref().is_some_and(|a| !platform.arch.matches(a)) {
+        return false;
+    }
+    match os.version.as_deref() {
+        // Regex invalide : la règle ne s'applique pas.
+        Some(re) => Regex::new(re).is_ok_and(|re| re.is_match(&platform.os_version)),
+        None => true,
+    }
+}
+
+fn rule_matches(rule: &Rule, platform: &Platform, features: &Features) -> bool {
+    let os_ok = rule.os.as_ref().is_none_or(|os| os_matches(os, platform));
+    let features_ok = rule
+        .features
+        .as_ref()
+        .is_none_or(|f| f.iter().all(|(name, want)| features.get(name) == *want));
+    os_ok && features_ok
+}
+
+/// Sémantique du launcher officiel : sans règles tout est permis ; sinon on part de
+/// « refusé » et la dernière règle qui correspond décide.
+pub fn rules_allow(rules: &[Rule], platform: &Platform, features: &Features) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+    rules
+        .iter()
+        .rfind(|r| rule_matches(r, platform, features))
+        .is_some_and(|r| r.action == RuleAction::Allow)
+}
+
+pub fn library_applies(lib: &Library, platform: &Platform) -> bool {
+    lib.rules
+        .as_deref()
+        .is_none_or(|rules| rules_allow(rules, platform, &Features::default()))
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::download::{sha1_hex, Downloader, FileTask, ProgressFn};
+use super::install::{safe_relative, GameDir};
+use super::jdk::{self, DetectedJdk};
+use super::rules::{Arch, OsName, Platform};
+use super::version::{JavaVersion, VersionJson};
+use super::McError;
+
+const RUNTIME_INDEX_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// Versions sans `javaVersion` (≤ 1.16) : Java 8 « jre-legacy ».
+const LEGACY_COMPONENT: &str = "jre-legacy";
+const LEGACY_MAJOR: u32 = 8;
+
+/* ===================== Index des runtimes (all.json) ===================== */
+
+// plateforme → composant → variantes (en pratique une seule)
+type RuntimeIndex = HashMap<String, HashMap<String, Vec<RuntimeEntry>>>;
+
+#[derive(Debug, Deserialize)]
+struct RuntimeEntry {
+    manifest: RuntimeManifestRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeManifestRef {
+    sha1: String,
+    url: String,
+}
+
+/* ===================== Manifeste d'un composant ===================== */
+
+#[derive(Debug, Deserialize)]
+struct ComponentManifest {
+    files: HashMap<String, ComponentFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+// `executable` et `target` ne servent qu'aux systèmes Unix.
+#[cfg_attr(not(unix), allow(dead_code))]
+enum ComponentFile {
+    Directory,
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: ComponentDownloads,
+    },
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct ComponentDownloads {
+    raw: RawDownload,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDownload {
+    sha1: String,
+    size: u64,
+    url: String,
+}
+
+/* ===================== Plateforme / chemins ===================== */
+
+/// Clé de plateforme du manifeste Mojang (`windows-x64`, `mac-os-arm64`, ...).
+pub fn runtime_platform_key(platform: &Platform) -> Option<&'static str> {
+    match (platform.os, platform.arch) {
+        (OsName::Linux, Arch::X86_64) => Some("linux"),
+        (OsName::Linux, Arch::X86) => Some("linux-i386"),
+        (OsName::Linux, Arch::Aarch64) => None,
+        (OsName::Windows, Arch::X86_64) => Some("windows-x64"),
+        (OsName::Windows, Arch::X86) => Some("windows-x86"),
+        (OsName::Windows, Arch::Aarch64) => Some("windows-arm64"),
+        (OsName::Osx, Arch::Aarch64) => Some("mac-os-arm64"),
+        (OsName::Osx, _) => Some("mac-os"),
+    }
+}
+
+/// Composant requis par la version (`java-runtime-gamma`, `jre-legacy`, ...).
+pub fn required_java(version: &VersionJson) -> JavaVersion {
+    version.java_version.clone().unwrap_or_else(|| JavaVersion {
+        component: LEGACY_COMPONENT.to_string(),
+        major_version: LEGACY_MAJOR,
+    })
+}
+
+pub fn runtime_dir(dirs: &GameDir, component: &str) -> PathBuf {
+    dirs.root().join("runtime").join(component)
+}
+
+/// Exécutable java à l'intérieur d'un runtime Mojang.
+pub fn java_binary(runtime_dir: &Path, platform: &Platform) -> PathBuf {
+    match platform.os {
+        OsName::Windows => runtime_dir.join("bin").join("java.exe"),
+        OsName::Osx => runtime_dir.join("jre.bundle/Contents/Home/bin/java"),
+        OsName::Linux => runtime_dir.join("bin").join("java"),
+    }
+}
+
+/* ===================== Installation ===================== */
+
+#[cfg(unix)]
+fn finish_entries(dir: &Path, manifest: &ComponentManifest) -> Result<(), McError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    for (name, file) in &manifest.files {
+        let Some(rel) = safe_relative(name) else {
+            continue;
+        };
+        let path = dir.join(rel);
+        let res = match file {
+            ComponentFile::File { executable: true, .. } => {
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            }
+            ComponentFile::Link { target } => {
+                let _ = std::fs::remove_file(&path);
+                std::os::unix::fs::symlink(target, &path)
+            }
+            _ => Ok(()),
+        };
+        res.map_err(|e| McError::Io(format!("{}: {e}", path.display())))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn finish_entries(_dir: &Path, _manifest: &ComponentManifest) -> Result<(), McError> {
+    // Pas de bit exécutable ni de liens à poser sous Windows.
+    Ok(())
+}
+
+/// Java local à utiliser quand Mojang ne publie pas de runtime pour la plateforme : le premier
+/// JDK compatible de `jdks` (triés par `jdk::discover`), sinon une erreur invitant à en installer un.
+pub fn local_java(jdks: &[DetectedJdk], platform: &Platform, java: &JavaVersion) -> Result<PathBuf, McError> {
+    jdks.iter()
+        .find(|jdk| jdk.compatible)
+        .map(|jdk| PathBuf::from(&jdk.path))
+        .ok_or_else(|| {
+            McError::Runtime(format!(
+                "pas de runtime Mojang pour {:?}/{:?} : installez un JDK {} pour cette architecture",
+                platform.os, platform.arch, java.major_version
+            ))
+        })
+}
+
+/// Installe (si besoin) le runtime Java exigé par la version et renvoie le chemin de `java`.
+/// Un runtime déjà installé (marqueur `.sha1` + exécutable présents) est réutilisé sans
+/// contacter launchermeta : le lancement hors ligne reste possible. Sans runtime Mojang pour
+/// la plateforme (Linux ARM), un JDK local compatible prend le relais.
+pub async fn ensure_runtime(
+    dl: &Downloader,
+    dirs: &GameDir,
+    platform: &Platform,
+    java: &JavaVersion,
+    on_progress: ProgressFn,
+) -> Result<PathBuf, McError> {
+    let Some(key) = runtime_platform_key(platform) else {
+        let (dirs, host, major) = (dirs.clone(), platform.clone(), java.major_version);
+        let jdks = tokio::task::spawn_blocking(move || jdk::discover(&dirs, &host, major))
+            .await
+            .map_err(|e| McError::Io(e.to_string()))?;
+        return local_java(&jdks, platform, java);
+    };
+
+    let dir = runtime_dir(dirs, &java.component);
+    let java_bin = java_binary(&dir, platform);
+    let marker = dir.join(".sha1");
+    let installed = tokio::fs::read_to_string(&marker).await.unwrap_or_default();
+    if !installed.trim().is_empty() && java_bin.exists() {
+        return Ok(java_bin);
+    }
+
+    let index_data = dl.fetch(RUNTIME_INDEX_URL).await?;
+    let index: RuntimeIndex =
+        serde_json::from_slice(&index_data).map_err(|e| McError::Json(e.to_string()))?;
+    let entry = index
+        .get(key)
+        .and_then(|components| components.get(&java.component))
+        .and_then(|entries| entries.first())
+        .ok_or_else(|| McError::Runtime(format!("composant {} indisponible pour {key}", java.component)))?;
+
+    let manifest_data = dl.fetch(&entry.manifest.url).await?;
+    let got = sha1_hex(&manifest_data);
+    if !got.eq_ignore_ascii_case(&entry.manifest.sha1) {
+        return Err(McError::Integrity(format!(
+            "{}: sha1 attendu {}, obtenu {got}",
+            entry.manifest.url, entry.manifest.sha1
+        )));
+    }
+    let manifest: ComponentManifest =
+        serde_json::from_slice(&manifest_data).map_err(|e| McError::Json(e.to_string()))?;
+
+    let mut tasks = Vec::new();
+    for (name, file) in &manifest.files {
+        let Some(rel) = safe_relative(name) else {
+            continue;
+        };
+        let path = dir.join(rel);
+        match file {
+            ComponentFile::Directory => {
+                tokio::fs::create_dir_all(&path)
+                    .await
+                    .map_err(|e| McError::Io(format!("{}: {e}", path.display())))?;
+            }
+            ComponentFile::File { downloads, .. } => tasks.push(FileTask {
+                url: downloads.raw.url.clone(),
+                path,
+                sha1: downloads.raw.sha1.clone(),
+                size: downloads.raw.size,
+            }),
+            ComponentFile::Link { .. } => {}
+        }
+    }
+    dl.download_all(tasks, on_progress).await?;
+
+    let finish_dir = dir.clone();
+    tokio::task::spawn_blocking(move || finish_entries(&finish_dir, &manifest))
+        .await
+        .map_err(|e| McError::Io(e.to_string()))??;
+
+    tokio::fs::write(&marker, &entry.manifest.sha1)
+        .await
+        .map_err(|e| McError::Io(format!("{}: {e}", marker.display())))?;
+    Ok(java_bin)
+}
@@ -0,0 +1,235 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::auth::{self, AuthContext, McProfileLite};
+use super::auth_error::AuthError;
+use super::profile::{self, SkinVariant};
+use super::skin_render::{self, SkinTexture};
+
+/* ===================== Bibliothèque locale (app data/skins) ===================== */
+// `library.json` décrit les entrées ; chacune a sa texture `<id>.png` et ses aperçus
+// `<id>.head.png` / `<id>.body.png`, générés à l'import.
+
+const INDEX_FILE: &str = "library.json";
+/// Agrandissement des aperçus : tête 64x64, corps 128x256.
+const PREVIEW_SCALE: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkinEntry {
+    pub id: String,
+    pub name: String,
+    pub variant: SkinVariant,
+    /// URL d'origine (skin importé depuis le profil), absente pour un fichier local.
+    pub source_url: Option<String>,
+    /// Secondes Unix.
+    pub added_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkinView {
+    Head,
+    Body,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryIndex {
+    skins: Vec<SkinEntry>,
+}
+
+pub struct SkinLibrary {
+    dir: PathBuf,
+}
+
+fn io_err(path: &Path, e: impl std::fmt::Display) -> AuthError {
+    AuthError::SkinLibrary(format!("{}: {e}", path.display()))
+}
+
+impl SkinLibrary {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn from_app(app: &AppHandle) -> Result<Self, AuthError> {
+        let data = app.path().app_data_dir().map_err(|e| AuthError::SkinLibrary(e.to_string()))?;
+        Ok(Self::new(data.join("skins")))
+    }
+
+    fn file(&self, id: &str, suffix: &str) -> PathBuf {
+        self.dir.join(format!("{id}{suffix}"))
+    }
+
+    fn load(&self) -> Result<LibraryIndex, AuthError> {
+        let path = self.dir.join(INDEX_FILE);
+        match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| io_err(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(LibraryIndex::default()),
+            Err(e) => Err(io_err(&path, e)),
+        }
+    }
+
+    fn save(&self, index: &LibraryIndex) -> Result<(), AuthError> {
+        let path = self.dir.join(INDEX_FILE);
+        let data = serde_json::to_vec_pretty(index).map_err(|e| io_err(&path, e))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| io_err(&tmp, e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| io_err(&path, e))
+    }
+
+    /// Entrées de la plus récente à la plus ancienne.
+    pub fn list(&self) -> Result<Vec<SkinEntry>, AuthError> {
+        let mut skins = self.load()?.skins;
+        skins.sort_by_key(|s| Reverse(s.added_at));
+        Ok(skins)
+    }
+
+    /// Seuls les ids présents dans l'index mènent à un fichier.
+    pub fn get(&self, id: &str) -> Result<SkinEntry, AuthError> {
+        self.load()?
+            .skins
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AuthError::SkinLibrary(format!("skin inconnu: {id}")))
+    }
+
+    /// Valide la texture, l'enregistre avec ses aperçus et l'ajoute à l'index.
+    pub fn import(
+        &self,
+        png: &[u8],
+        name: &str,
+        variant: SkinVariant,
+        source_url: Option<String>,
+    ) -> Result<SkinEntry, AuthError> {
+        profile::validate_skin_png(png)?;
+        let tex = SkinTexture::decode(png).map_err(AuthError::InvalidSkin)?;
+        let head = skin_render::render_head(&tex, PREVIEW_SCALE).map_err(AuthError::SkinLibrary)?;
+        let body = skin_render::render_body(&tex, variant, PREVIEW_SCALE).map_err(AuthError::SkinLibrary)?;
+
+        std::fs::create_dir_all(&self.dir).map_err(|e| io_err(&self.dir, e))?;
+        let entry = SkinEntry {
+            id: format!("{:016x}", rand::random::<u64>()),
+            name: match name.trim() {
+                "" => "Skin".to_string(),
+                name => name.to_string(),
+            },
+            variant,
+            source_url,
+            added_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        };
+        for (suffix, data) in [(".png", png), (".head.png", &head[..]), (".body.png", &body[..])] {
+            let path = self.file(&entry.id, suffix);
+            std::fs::write(&path, data).map_err(|e| io_err(&path, e))?;
+        }
+        let mut index = self.load()?;
+        index.skins.push(entry.clone());
+        self.save(&index)?;
+        Ok(entry)
+    }
+
+    pub fn texture(&self, id: &str) -> Result<Vec<u8>, AuthError> {
+        let entry = self.get(id)?;
+        let path = self.file(&entry.id, ".png");
+        std::fs::read(&path).map_err(|e| io_err(&path, e))
+    }
+
+    /// Aperçu PNG ; régénéré depuis la texture s'il manque.
+    pub fn preview(&self, id: &str, view: SkinView) -> Result<Vec<u8>, AuthError> {
+        let entry = self.get(id)?;
+        let suffix = match view {
+            SkinView::Head => ".head.png",
+            SkinView::Body => ".body.png",
+        };
+        let path = self.file(&entry.id, suffix);
+        if let Ok(data) = std::fs::read(&path) {
+            return Ok(data);
+        }
+        let tex = SkinTexture::decode(&self.texture(id)?).map_err(AuthError::InvalidSkin)?;
+        let data = match view {
+            SkinView::Head => skin_render::render_head(&tex, PREVIEW_SCALE),
+            SkinView::Body => skin_render::render_body(&tex, entry.variant, PREVIEW_SCALE),
+        }
+        .map_err(AuthError::SkinLibrary)?;
+        std::fs::write(&path, &data).map_err(|e| io_err(&path, e))?;
+        Ok(data)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), AuthError> {
+        let mut index = self.load()?;
+        let before = index.skins.len();
+        index.skins.retain(|s| s.id != id);
+        if index.skins.len() == before {
+            return Err(AuthError::SkinLibrary(format!("skin inconnu: {id}")));
+        }
+        self.save(&index)?;
+        for suffix in [".png", ".head.png", ".body.png"] {
+            let _ = std::fs::remove_file(self.file(id, suffix));
+        }
+        Ok(())
+    }
+}
+
+/// Télécharge le skin actif du profil et l'ajoute à la bibliothèque.
+pub async fn import_current(library: &SkinLibrary, ctx: &AuthContext, name: Option<String>) -> Result<SkinEntry, AuthError> {
+    let profile = auth::fetch_profile(ctx).await?;
+    let url = profile
+        .skin_url
+        .ok_or_else(|| AuthError::InvalidSkin("aucun skin actif sur le profil".into()))?;
+    let res = ctx.http.get(&url).send().await?;
+    let status = res.status();
+    if !status.is_success() {
+        return Err(AuthError::Service(format!("skin download failed: {status} – {url}")));
+    }
+    let png = res.bytes().await?;
+    let name = name.unwrap_or(profile.name);
+    library.import(&png, &name, profile.skin_variant.unwrap_or(SkinVariant::Classic), Some(url))
+}
+
+/* ===================== Commandes ===================== */
+
+#[tauri::command]
+pub fn skin_library_list(app: AppHandle) -> Result<Vec<SkinEntry>, AuthError> {
+    SkinLibrary::from_app(&app)?.list()
+}
+
+/// Import d'un fichier choisi côté front (octets du PNG).
+#[tauri::command]
+pub fn skin_library_import_file(
+    app: AppHandle,
+    png: Vec<u8>,
+    name: String,
+    variant: SkinVariant,
+) -> Result<SkinEntry, AuthError> {
+    SkinLibrary::from_app(&app)?.import(&png, &name, variant, None)
+}
+
+/// Import du skin porté par le compte actif (nom par défaut : le pseudo).
+#[tauri::command]
+pub async fn skin_library_import_current(app: AppHandle, name: Option<String>) -> Result<SkinEntry, AuthError> {
+    import_current(&SkinLibrary::from_app(&app)?, &AuthContext::from_app(&app)?, name).await
+}
+
+/// Aperçu en data URL, directement utilisable dans un `<img>`.
+#[tauri::command]
+pub fn skin_library_preview(app: AppHandle, id: String, view: SkinView) -> Result<String, AuthError> {
+    let png = SkinLibrary::from_app(&app)?.preview(&id, view)?;
+    Ok(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(png)))
+}
+
+/// Envoie un skin de la bibliothèque sur le compte actif.
+#[tauri::command]
+pub async fn skin_library_apply(app: AppHandle, id: String) -> Result<McProfileLite, AuthError> {
+    let library = SkinLibrary::from_app(&app)?;
+    let entry = library.get(&id)?;
+    profile::upload_skin(&AuthContext::from_app(&app)?, library.texture(&id)?, entry.variant).await
+}
+
+#[tauri::command]
+pub fn skin_library_remove(app: AppHandle, id: String) -> Result<(), AuthError> {
+    SkinLibrary::from_app(&app)?.remove(&id)
+}
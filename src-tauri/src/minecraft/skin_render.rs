@@ -0,0 +1,193 @@
+use std::io::Cursor;
+
+use super::profile::SkinVariant;
+
+/* ===================== Texture de skin (RGBA 64x64) ===================== */
+
+/// Skin décodé en RGBA 8 bits, toujours au format 64x64 (les anciens 64x32 sont complétés).
+pub struct SkinTexture {
+    rgba: Vec<u8>,
+    /// Ancien format : pas de calques hors chapeau, membres gauches copiés des droits.
+    legacy: bool,
+}
+
+impl SkinTexture {
+    pub fn decode(png_data: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(Cursor::new(png_data));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| format!("png illisible: {e}"))?;
+        let size = reader.output_buffer_size().ok_or("png trop grand")?;
+        let mut buf = vec![0; size];
+        let frame = reader.next_frame(&mut buf).map_err(|e| format!("png illisible: {e}"))?;
+        let (width, height) = (frame.width, frame.height);
+        if width != 64 || (height != 64 && height != 32) {
+            return Err(format!("{width}x{height}, attendu 64x64 ou 64x32"));
+        }
+
+        let channels = match frame.color_type {
+            png::ColorType::Rgba => 4,
+            png::ColorType::Rgb => 3,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Grayscale => 1,
+            other => return Err(format!("format de couleur non géré: {other:?}")),
+        };
+        let mut rgba = vec![0u8; 64 * 64 * 4];
+        for (i, px) in buf[..frame.buffer_size()].chunks_exact(channels).enumerate() {
+            let px = match *px {
+                [r, g, b, a] => [r, g, b, a],
+                [r, g, b] => [r, g, b, 255],
+                [l, a] => [l, l, l, a],
+                [l] => [l, l, l, 255],
+                _ => unreachable!(),
+            };
+            rgba[i * 4..i * 4 + 4].copy_from_slice(&px);
+        }
+
+        let mut tex = Self { rgba, legacy: height == 32 };
+        if tex.legacy {
+            tex.fill_legacy_limbs();
+        }
+        Ok(tex)
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * 64 + x) * 4) as usize;
+        [self.rgba[i], self.rgba[i + 1], self.rgba[i + 2], self.rgba[i + 3]]
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, px: [u8; 4]) {
+        let i = ((y * 64 + x) * 4) as usize;
+        self.rgba[i..i + 4].copy_from_slice(&px);
+    }
+
+    /// Face avant des membres gauches = face avant des membres droits en miroir, comme le jeu.
+    fn fill_legacy_limbs(&mut self) {
+        for (from, to) in [((44, 20), (36, 52)), ((4, 20), (20, 52))] {
+            for dy in 0..12 {
+                for dx in 0..4 {
+                    let px = self.pixel(from.0 + 3 - dx, from.1 + dy);
+                    self.set_pixel(to.0 + dx, to.1 + dy, px);
+                }
+            }
+        }
+    }
+
+    /// Un chapeau entièrement opaque sur un ancien skin est ignoré (fond noir des vieux éditeurs).
+    fn hat_is_opaque(&self) -> bool {
+        (32..64).all(|x| (0..16).all(|y| self.pixel(x, y)[3] == 255))
+    }
+}
+
+/* ===================== Rendus à plat ===================== */
+
+/// Zone de la texture (x, y, largeur, hauteur) copiée à la position (x, y) de l'aperçu.
+struct Part {
+    src: (u32, u32, u32, u32),
+    dst: (u32, u32),
+}
+
+const fn part(sx: u32, sy: u32, w: u32, h: u32, dx: u32, dy: u32) -> Part {
+    Part { src: (sx, sy, w, h), dst: (dx, dy) }
+}
+
+/// Aperçu en pixels de skin, agrandi ensuite par `scale`.
+struct Canvas {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self { width, height, rgba: vec![0; (width * height * 4) as usize] }
+    }
+
+    /// Calque de base : opaque. Calque externe : mélangé selon son alpha.
+    fn draw(&mut self, tex: &SkinTexture, part: &Part, overlay: bool) {
+        let (sx, sy, w, h) = part.src;
+        for y in 0..h {
+            for x in 0..w {
+                let src = tex.pixel(sx + x, sy + y);
+                let i = (((part.dst.1 + y) * self.width + part.dst.0 + x) * 4) as usize;
+                let dst = &mut self.rgba[i..i + 4];
+                if !overlay {
+                    dst.copy_from_slice(&[src[0], src[1], src[2], 255]);
+                    continue;
+                }
+                let a = u32::from(src[3]);
+                for c in 0..3 {
+                    dst[c] = ((u32::from(src[c]) * a + u32::from(dst[c]) * (255 - a)) / 255) as u8;
+                }
+                dst[3] = dst[3].max(src[3]);
+            }
+        }
+    }
+
+    fn encode(&self, scale: u32) -> Result<Vec<u8>, String> {
+        let scale = scale.max(1);
+        let (w, h) = (self.width * scale, self.height * scale);
+        let mut scaled = Vec::with_capacity((w * h * 4) as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let i = (((y / scale) * self.width + x / scale) * 4) as usize;
+                scaled.extend_from_slice(&self.rgba[i..i + 4]);
+            }
+        }
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, w, h);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&scaled).map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(out)
+    }
+}
+
+/// Visage (face avant de la tête + chapeau), 8x8 pixels de skin.
+pub fn render_head(tex: &SkinTexture, scale: u32) -> Result<Vec<u8>, String> {
+    let mut canvas = Canvas::new(8, 8);
+    canvas.draw(tex, &part(8, 8, 8, 8, 0, 0), false);
+    if !(tex.legacy && tex.hat_is_opaque()) {
+        canvas.draw(tex, &part(40, 8, 8, 8, 0, 0), true);
+    }
+    canvas.encode(scale)
+}
+
+/// Corps entier vu de face, 16x32 pixels de skin. Le bras droit du joueur est à gauche de l'image.
+pub fn render_body(tex: &SkinTexture, variant: SkinVariant, scale: u32) -> Result<Vec<u8>, String> {
+    let arm = match variant {
+        SkinVariant::Classic => 4,
+        SkinVariant::Slim => 3,
+    };
+    let right_x = 4 - arm;
+    let base = [
+        part(8, 8, 8, 8, 4, 0),            // tête
+        part(20, 20, 8, 12, 4, 8),         // torse
+        part(44, 20, arm, 12, right_x, 8), // bras droit
+        part(36, 52, arm, 12, 12, 8),      // bras gauche
+        part(4, 20, 4, 12, 4, 20),         // jambe droite
+        part(20, 52, 4, 12, 8, 20),        // jambe gauche
+    ];
+    let overlays = [
+        part(20, 36, 8, 12, 4, 8),
+        part(44, 36, arm, 12, right_x, 8),
+        part(52, 52, arm, 12, 12, 8),
+        part(4, 36, 4, 12, 4, 20),
+        part(4, 52, 4, 12, 8, 20),
+    ];
+
+    let mut canvas = Canvas::new(16, 32);
+    for p in &base {
+        canvas.draw(tex, p, false);
+    }
+    if !(tex.legacy && tex.hat_is_opaque()) {
+        canvas.draw(tex, &part(40, 8, 8, 8, 4, 0), true);
+    }
+    if !tex.legacy {
+        for p in &overlays {
+            canvas.draw(tex, p, true);
+        }
+    }
+    canvas.encode(scale)
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/* ===================== JSON de version (versions/<id>/<id>.json) ===================== */
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionJson {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub main_class: String,
+    /// Format moderne (1.13+) : arguments structurés avec règles.
+    pub arguments: Option<Arguments>,
+    /// Format historique (≤ 1.12) : une seule chaîne d'arguments de jeu.
+    pub minecraft_arguments: Option<String>,
+    pub downloads: VersionDownloads,
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    pub asset_index: AssetIndexRef,
+    pub assets: Option<String>,
+    pub java_version: Option<JavaVersion>,
+    pub logging: Option<Logging>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionDownloads {
+    pub client: DownloadInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DownloadInfo {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetIndexRef {
+    pub id: String,
+    pub sha1: String,
+    pub size: u64,
+    pub total_size: Option<u64>,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaVersion {
+    pub component: String,
+    pub major_version: u32,
+}
+
+/* ===================== Arguments & règles ===================== */
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Arguments {
+    #[serde(default)]
+    pub game: Vec<Argument>,
+    #[serde(default)]
+    pub jvm: Vec<Argument>,
+}
+
+/// Soit une chaîne simple, soit une (ou plusieurs) valeur(s) soumise(s) à des règles.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Argument {
+    Plain(String),
+    Conditional { rules: Vec<Rule>, value: ArgValue },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ArgValue {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ArgValue {
+    pub fn values(&self) -> &[String] {
+        match self {
+            ArgValue::One(s) => std::slice::from_ref(s),
+            ArgValue::Many(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub action: RuleAction,
+    pub os: Option<OsRule>,
+    pub features: Option<HashMap<String, bool>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Disallow,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OsRule {
+    pub name: Option<String>,
+    pub arch: Option<String>,
+    /// Expression régulière sur la version de l'OS (ex. `^10\\.`).
+    pub version: Option<String>,
+}
+
+/* ===================== Logging (log4j) ===================== */
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Logging {
+    pub client: Option<LoggingConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoggingConfig {
+    /// Ex. `-Dlog4j.configurationFile=${path}`.
+    pub argument: String,
+    pub file: LoggingFile,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoggingFile {
+    pub id: String,
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+/* ===================== Librairies ===================== */
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Library {
+    pub name: String,
+    pub downloads: Option<LibraryDownloads>,
+    pub rules: Option<Vec<Rule>>,
+    /// Classifier de natives par OS (`"linux": "natives-linux"`, `"windows": "natives-windows-${arch}"`).
+    pub natives: Option<HashMap<String, String>>,
+    pub extract: Option<Extract>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LibraryDownloads {
+    pub artifact: Option<Artifact>,
+    pub classifiers: Option<HashMap<String, Artifact>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Artifact {
+    pub path: String,
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Extract {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
@@ -1,34 +1,59 @@
 use tauri::{Manager};
 use base64::{engine::general_purpose, Engine as _};
 
-#[tauri::command]
-pub fn ks_get_device_secret(app: tauri::AppHandle) -> Result<String, String> {
+/// Secret d'appareil (32 octets, base64) : trousseau natif, sinon fichier local.
+/// Clé du coffre `EncryptedFileStore` : ne quitte jamais le processus Rust. Le repli fichier
+/// est écrit en 0600 (limites décrites sur `EncryptedFileStore`).
+pub fn device_secret(app: &tauri::AppHandle) -> Result<String, String> {
     // 1) Trousseau natif (Windows Credential Manager / libsecret, etc.)
     let service = "fr.djinn.kashirlauncher";
     let user = "stronghold_device_secret";
-    let entry = keyring::Entry::new(service, user).map_err(|e| e.to_string())?;
+    let entry = keyring::Entry::new(service, user).ok();
 
-    if let Ok(secret) = entry.get_password() {
+    if let Some(secret) = entry.as_ref().and_then(|e| e.get_password().ok()) {
         return Ok(secret);
     }
 
-    // 2) Génère une clé 32 bytes (CSPRNG)
+    // 2) Fallback fichier local déjà créé lors d'un précédent lancement
+    let path = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("device_secret.b64");
+    if let Ok(secret) = std::fs::read_to_string(&path) {
+        // Fichiers créés avant le passage en 0600.
+        restrict_to_owner(&path);
+        return Ok(secret.trim().to_string());
+    }
+
+    // 3) Génère une clé 32 bytes (CSPRNG)
     let mut buf = [0u8; 32];
     getrandom::fill(&mut buf).map_err(|e| e.to_string())?;
     let secret = general_purpose::STANDARD_NO_PAD.encode(buf);
 
-    // 3) Sauvegarde dans le trousseau, sinon fallback fichier local
-    if let Err(_e) = entry.set_password(&secret) {
-        let path = app
-            .path()
-            .app_local_data_dir()
-            .map_err(|e| e.to_string())?
-            .join("device_secret.b64");
+    // 4) Sauvegarde dans le trousseau, sinon fallback fichier local
+    let stored = entry.is_some_and(|e| e.set_password(&secret).is_ok());
+    if !stored {
         if let Some(dir) = path.parent() {
             std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
         }
-        std::fs::write(&path, &secret).map_err(|e| e.to_string())?;
+        // Écriture puis renommage : jamais de fichier à moitié écrit ni lisible par d'autres.
+        let tmp = path.with_extension("b64.tmp");
+        std::fs::write(&tmp, &secret).map_err(|e| e.to_string())?;
+        restrict_to_owner(&tmp);
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
     }
 
     Ok(secret)
 }
+
+#[cfg(unix)]
+fn restrict_to_owner(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_path: &std::path::Path) {
+    // Le dossier de données local est déjà propre à l'utilisateur sous Windows.
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use kashirlauncher_lib::args::{self, LaunchOptions, Session};
+use kashirlauncher_lib::assets::AssetLayout;
+use kashirlauncher_lib::install::GameDir;
+use kashirlauncher_lib::rules::{Arch, OsName, Platform};
+use kashirlauncher_lib::version::VersionJson;
+
+const UUID: &str = "069a79f444e94726a5befca90e38aaf5";
+
+/// JSON de version capturés chez Mojang, réduits à quelques librairies représentatives.
+fn fixture(id: &str) -> VersionJson {
+    let raw = match id {
+        "1.7.2" => include_str!("fixtures/1.7.2.json"),
+        "1.12.2" => include_str!("fixtures/1.12.2.json"),
+        "1.20.4" => include_str!("fixtures/1.20.4.json"),
+        _ => unreachable!(),
+    };
+    serde_json::from_str(raw).unwrap()
+}
+
+fn root() -> PathBuf {
+    PathBuf::from("/games/mc")
+}
+
+fn natives() -> PathBuf {
+    PathBuf::from("/tmp/natives-1")
+}
+
+fn show(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn lib(rel: &str) -> String {
+    show(&root().join("libraries").join(rel))
+}
+
+fn linux() -> Platform {
+    Platform { os: OsName::Linux, arch: Arch::X86_64, os_version: "6.8.0".into() }
+}
+
+fn options(platform: Platform, layout: AssetLayout) -> LaunchOptions {
+    LaunchOptions {
+        natives_dir: natives(),
+        asset_layout: layout,
+        session: Some(Session {
+            player_name: "Kashir".into(),
+            uuid: UUID.into(),
+            access_token: "mc-access".into(),
+            user_type: "msa".into(),
+            xuid: Some("2535428888".into()),
+        }),
+        ram_mb: 2048,
+        platform,
+        demo: false,
+        resolution: None,
+        quick_play_server: None,
+    }
+}
+
+fn argv(id: &str, opts: &LaunchOptions) -> Vec<String> {
+    args::build_argv(&GameDir::new(root()), &fixture(id), opts)
+}
+
+#[test]
+fn substitute_leaves_unknown_and_unterminated_placeholders() {
+    let vars = HashMap::from([("a", "1".to_string()), ("b", String::new())]);
+    assert_eq!(args::substitute("${a}-${b}-${c}", &vars), "1--${c}");
+    assert_eq!(args::substitute("x=${a}${", &vars), "x=1${");
+    assert_eq!(args::substitute("sans variable", &vars), "sans variable");
+}
+
+#[test]
+fn legacy_1_7_virtual_assets() {
+    let version = fixture("1.7.2");
+    assert!(version.arguments.is_none());
+    assert_eq!(version.asset_index.id, "legacy");
+
+    let cp = [
+        lib("net/sf/jopt-simple/jopt-simple/4.5/jopt-simple-4.5.jar"),
+        lib("org/lwjgl/lwjgl/lwjgl/2.9.0/lwjgl-2.9.0.jar"),
+        show(&root().join("versions/1.7.2/1.7.2.jar")),
+    ]
+    .join(":");
+    let expected = [
+        "-Xmx2048m".to_string(),
+        "-XX:+UseG1GC".into(),
+        format!("-Djava.library.path={}", show(&natives())),
+        "-cp".into(),
+        cp,
+        "net.minecraft.client.main.Main".into(),
+        "--username".into(),
+        "Kashir".into(),
+        "--session".into(),
+        format!("token:mc-access:{UUID}"),
+        "--version".into(),
+        "1.7.2".into(),
+        "--gameDir".into(),
+        show(&root()),
+        "--assetsDir".into(),
+        show(&root().join("assets/virtual/legacy")),
+        "--assetIndex".into(),
+        "legacy".into(),
+        "--uuid".into(),
+        UUID.into(),
+        "--accessToken".into(),
+        "mc-access".into(),
+        "--userProperties".into(),
+        "{}".into(),
+        "--userType".into(),
+        "msa".into(),
+    ];
+    assert_eq!(argv("1.7.2", &options(linux(), AssetLayout::Virtual)), expected);
+
+    // Sans règles dans le format historique : démo et résolution ajoutées à la fin.
+    let opts = LaunchOptions { demo: true, resolution: Some((854, 480)), ..options(linux(), AssetLayout::Virtual) };
+    let with_features = argv("1.7.2", &opts);
+    assert_eq!(with_features[..expected.len()], expected);
+    assert_eq!(with_features[expected.len()..], ["--demo", "--width", "854", "--height", "480"]);
+}
+
+#[test]
+fn legacy_1_12_with_log_config() {
+    let cp = [
+        lib("com/mojang/patchy/1.1/patchy-1.1.jar"),
+        lib("com/mojang/text2speech/1.10.3/text2speech-1.10.3.jar"),
+        lib("org/lwjgl/lwjgl/lwjgl/2.9.2-nightly-20140822/lwjgl-2.9.2-nightly-20140822.jar"),
+        show(&root().join("versions/1.12.2/1.12.2.jar")),
+    ]
+    .join(":");
+    let expected = [
+        "-Xmx2048m".to_string(),
+        "-XX:+UseG1GC".into(),
+        format!("-Djava.library.path={}", show(&natives())),
+        "-cp".into(),
+        cp,
+        format!("-Dlog4j.configurationFile={}", show(&root().join("assets/log_configs/client-1.12.xml"))),
+        "net.minecraft.client.main.Main".into(),
+        "--username".into(),
+        "Kashir".into(),
+        "--version".into(),
+        "1.12.2".into(),
+        "--gameDir".into(),
+        show(&root()),
+        "--assetsDir".into(),
+        show(&root().join("assets")),
+        "--assetIndex".into(),
+        "1.12".into(),
+        "--uuid".into(),
+        UUID.into(),
+        "--accessToken".into(),
+        "mc-access".into(),
+        "--userType".into(),
+        "msa".into(),
+        "--versionType".into(),
+        "release".into(),
+    ];
+    assert_eq!(argv("1.12.2", &options(linux(), AssetLayout::Hashed)), expected);
+}
+
+/// Arguments de jeu 1.20.4 communs à toutes les plateformes, features désactivées.
+fn modern_game_args() -> Vec<String> {
+    [
+        "net.minecraft.client.main.Main",
+        "--username",
+        "Kashir",
+        "--version",
+        "1.20.4",
+        "--gameDir",
+        &show(&root()),
+        "--assetsDir",
+        &show(&root().join("assets")),
+        "--assetIndex",
+        "12",
+        "--uuid",
+        UUID,
+        "--accessToken",
+        "mc-access",
+        "--clientId",
+        "",
+        "--xuid",
+        "2535428888",
+        "--userType",
+        "msa",
+        "--versionType",
+        "release",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn modern_jvm_props() -> Vec<String> {
+    let n = show(&natives());
+    vec![
+        format!("-Djava.library.path={n}"),
+        format!("-Djna.tmpdir={n}"),
+        format!("-Dorg.lwjgl.system.SharedLibraryExtractPath={n}"),
+        format!("-Dio.netty.native.workdir={n}"),
+        "-Dminecraft.launcher.brand=kashirlauncher".into(),
+        format!("-Dminecraft.launcher.version={}", env!("CARGO_PKG_VERSION")),
+    ]
+}
+
+fn log_arg() -> String {
+    format!("-Dlog4j.configurationFile={}", show(&root().join("assets/log_configs/client-1.12.xml")))
+}
+
+#[test]
+fn modern_1_20_on_linux_drops_feature_args() {
+    let cp = [
+        lib("com/mojang/logging/1.1.1/logging-1.1.1.jar"),
+        lib("org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2.jar"),
+        lib("org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2-natives-linux.jar"),
+        show(&root().join("versions/1.20.4/1.20.4.jar")),
+    ]
+    .join(":");
+    let mut expected = vec!["-Xmx2048m".to_string(), "-XX:+UseG1GC".into()];
+    expected.extend(modern_jvm_props());
+    expected.extend(["-cp".into(), cp, log_arg()]);
+    expected.extend(modern_game_args());
+    assert_eq!(argv("1.20.4", &options(linux(), AssetLayout::Hashed)), expected);
+}
+
+#[test]
+fn modern_1_20_on_windows_x86() {
+    let platform = Platform { os: OsName::Windows, arch: Arch::X86, os_version: "10.0.19045".into() };
+    let cp = [
+        lib("com/mojang/logging/1.1.1/logging-1.1.1.jar"),
+        lib("org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2.jar"),
+        lib("org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2-natives-windows.jar"),
+        lib("org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2-natives-windows-x86.jar"),
+        show(&root().join("versions/1.20.4/1.20.4.jar")),
+    ]
+    .join(";");
+    let mut expected = vec![
+        "-Xmx2048m".to_string(),
+        "-XX:+UseG1GC".into(),
+        "-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump".into(),
+        "-Xss1M".into(),
+    ];
+    expected.extend(modern_jvm_props());
+    expected.extend(["-cp".into(), cp, log_arg()]);
+    expected.extend(modern_game_args());
+    assert_eq!(argv("1.20.4", &options(platform, AssetLayout::Hashed)), expected);
+}
+
+#[test]
+fn modern_1_20_feature_args_when_enabled() {
+    let opts = LaunchOptions {
+        demo: true,
+        resolution: Some((1280, 720)),
+        quick_play_server: Some("play.example.net:25565".into()),
+        ..options(linux(), AssetLayout::Hashed)
+    };
+    let argv = argv("1.20.4", &opts);
+    let game = modern_game_args();
+    let tail = &argv[argv.len() - 7..];
+    assert_eq!(argv[argv.len() - 7 - game.len()..argv.len() - 7], game);
+    assert_eq!(
+        tail,
+        ["--demo", "--width", "1280", "--height", "720", "--quickPlayMultiplayer", "play.example.net:25565"]
+    );
+}
+
+#[test]
+fn preview_without_session_keeps_auth_placeholders() {
+    let opts = LaunchOptions { session: None, ..options(linux(), AssetLayout::Hashed) };
+    let argv = argv("1.20.4", &opts);
+    let at = |flag: &str| &argv[argv.iter().position(|a| a == flag).unwrap() + 1];
+    assert_eq!(at("--username"), "${auth_player_name}");
+    assert_eq!(at("--accessToken"), "${auth_access_token}");
+    assert_eq!(at("--version"), "1.20.4");
+}
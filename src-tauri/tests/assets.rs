@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use kashirlauncher_lib::assets::{self, AssetIndex, AssetLayout};
+use kashirlauncher_lib::download::sha1_hex;
+use kashirlauncher_lib::install::GameDir;
+use serde_json::json;
+
+/// Index minimal : `(nom lisible, contenu)` ; deux noms au même contenu partagent un hash.
+fn index(flags: serde_json::Value, files: &[(&str, &[u8])]) -> AssetIndex {
+    let mut value = flags;
+    let objects: serde_json::Map<_, _> = files
+        .iter()
+        .map(|(name, data)| (name.to_string(), json!({ "hash": sha1_hex(data), "size": data.len() })))
+        .collect();
+    value["objects"] = objects.into();
+    serde_json::from_value(value).unwrap()
+}
+
+/// Remplit le store `objects/xx/<hash>` comme après le téléchargement.
+fn seed_objects(dirs: &GameDir, files: &[(&str, &[u8])]) {
+    for (_, data) in files {
+        let path = assets::object_path(dirs, &sha1_hex(data));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+}
+
+const FILES: &[(&str, &[u8])] = &[
+    ("sounds/random/click.ogg", b"click"),
+    ("sounds/random/click_copy.ogg", b"click"),
+    ("lang/fr_FR.lang", b"menu.quit=Quitter"),
+    ("../escape.txt", b"dehors"),
+];
+
+#[test]
+fn layout_follows_index_flags() {
+    assert_eq!(index(json!({}), FILES).layout(), AssetLayout::Hashed);
+    assert_eq!(index(json!({ "virtual": true }), FILES).layout(), AssetLayout::Virtual);
+    assert_eq!(index(json!({ "map_to_resources": true }), FILES).layout(), AssetLayout::Resources);
+    // pre-1.6 déclare les deux : `resources/` l'emporte.
+    let both = index(json!({ "virtual": true, "map_to_resources": true }), FILES);
+    assert_eq!(both.layout(), AssetLayout::Resources);
+
+    let dirs = GameDir::new("/games/mc".into());
+    assert_eq!(assets::game_assets_dir(&dirs, "1.20", AssetLayout::Hashed), Path::new("/games/mc/assets"));
+    assert_eq!(
+        assets::game_assets_dir(&dirs, "legacy", AssetLayout::Virtual),
+        Path::new("/games/mc/assets/virtual/legacy")
+    );
+    assert_eq!(
+        assets::game_assets_dir(&dirs, "pre-1.6", AssetLayout::Resources),
+        Path::new("/games/mc/resources")
+    );
+}
+
+#[test]
+fn one_task_per_distinct_hash() {
+    let dirs = GameDir::new("/games/mc".into());
+    let mut tasks = assets::object_tasks(&dirs, &index(json!({}), FILES));
+    tasks.sort_by(|a, b| a.sha1.cmp(&b.sha1));
+
+    // click.ogg et click_copy.ogg : un seul objet.
+    let mut hashes: Vec<_> = FILES.iter().map(|(_, data)| sha1_hex(data)).collect();
+    hashes.sort();
+    hashes.dedup();
+    assert_eq!(tasks.iter().map(|t| t.sha1.clone()).collect::<Vec<_>>(), hashes);
+
+    let click = sha1_hex(b"click");
+    let task = tasks.iter().find(|t| t.sha1 == click).unwrap();
+    assert_eq!(task.path, Path::new("/games/mc/assets/objects").join(&click[..2]).join(&click));
+    assert_eq!(task.url, format!("https://resources.download.minecraft.net/{}/{click}", &click[..2]));
+    assert_eq!(task.size, 5);
+}
+
+#[test]
+fn virtual_index_is_materialized_under_assets_virtual() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    seed_objects(&dirs, FILES);
+    let index = index(json!({ "virtual": true }), FILES);
+
+    assets::materialize(&dirs, "legacy", &index).unwrap();
+
+    let base = dirs.assets().join("virtual/legacy");
+    assert_eq!(std::fs::read(base.join("sounds/random/click.ogg")).unwrap(), b"click");
+    assert_eq!(std::fs::read(base.join("sounds/random/click_copy.ogg")).unwrap(), b"click");
+    assert_eq!(std::fs::read(base.join("lang/fr_FR.lang")).unwrap(), b"menu.quit=Quitter");
+    // Nom qui sortirait du dossier : ignoré.
+    assert!(!dirs.assets().join("virtual/escape.txt").exists());
+    assert!(!dir.path().join("resources").exists());
+
+    // Lien physique vers le store quand le système de fichiers le permet.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let object = std::fs::metadata(assets::object_path(&dirs, &sha1_hex(b"click"))).unwrap();
+        let named = std::fs::metadata(base.join("sounds/random/click.ogg")).unwrap();
+        assert_eq!(object.ino(), named.ino());
+    }
+}
+
+#[test]
+fn map_to_resources_index_repairs_game_dir_resources() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    seed_objects(&dirs, FILES);
+    let index = index(json!({ "map_to_resources": true }), FILES);
+    let base = dir.path().join("resources");
+
+    // Fichier déjà présent à la bonne taille : conservé ; taille différente : remplacé.
+    std::fs::create_dir_all(base.join("lang")).unwrap();
+    std::fs::write(base.join("lang/fr_FR.lang"), b"menu.quit=QUITTER").unwrap();
+    std::fs::create_dir_all(base.join("sounds/random")).unwrap();
+    std::fs::write(base.join("sounds/random/click.ogg"), b"tronque").unwrap();
+
+    assets::materialize(&dirs, "pre-1.6", &index).unwrap();
+
+    assert_eq!(std::fs::read(base.join("lang/fr_FR.lang")).unwrap(), b"menu.quit=QUITTER");
+    assert_eq!(std::fs::read(base.join("sounds/random/click.ogg")).unwrap(), b"click");
+    assert_eq!(std::fs::read(base.join("sounds/random/click_copy.ogg")).unwrap(), b"click");
+    assert!(!dirs.assets().join("virtual").exists());
+}
+
+#[test]
+fn hashed_index_is_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    assets::materialize(&dirs, "12", &index(json!({}), FILES)).unwrap();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use common::*;
+use kashirlauncher_lib::accounts::AccountIndex;
+use kashirlauncher_lib::auth::{self, AuthContext, AuthState};
+use kashirlauncher_lib::auth_error::AuthError;
+use kashirlauncher_lib::profile;
+use serde_json::json;
+
+/* ===================== Scénarios ===================== */
+
+#[tokio::test]
+async fn device_code_login_then_profile_from_refresh() {
+    let mock = Mock::default();
+    mock.happy_path();
+    let h = Harness::new(&mock.serve().await);
+
+    let account = h.device_login().await.unwrap();
+    assert_eq!(account.uuid, UUID);
+    assert_eq!(account.name, "Kashir");
+    assert!(account.active);
+    assert_eq!(h.statuses(), ["success"]);
+
+    // Seul le refresh token est conservé, sous la clé du compte.
+    let creds = &h.ctx.credentials;
+    assert_eq!(creds.get(&format!("refresh.{UUID}")).unwrap().as_deref(), Some("ms-refresh-1"));
+    assert_eq!(creds.get("refresh.login.poll-1").unwrap(), None);
+
+    let poll = &mock.hits(TOKEN)[0];
+    assert!(poll.contains("device_code=dc-1"));
+    assert!(poll.contains("client_id=test-client"));
+
+    // Nouveau démarrage de l'app : caches vides, la chaîne repart du refresh token (rotation).
+    mock.reset(TOKEN, 200, json!({
+        "access_token": "ms-access-2",
+        "refresh_token": "ms-refresh-2",
+        "expires_in": 3600,
+        "token_type": "Bearer"
+    }));
+    let restarted = AuthContext { state: AuthState::default(), ..h.ctx.clone() };
+    let profile = auth::fetch_profile(&restarted).await.unwrap();
+    assert_eq!(profile.id, UUID);
+    assert_eq!(profile.skin_url.as_deref(), Some("https://textures.minecraft.net/texture/abc"));
+
+    let refresh = mock.hits(TOKEN).pop().unwrap();
+    assert!(refresh.contains("grant_type=refresh_token"));
+    assert!(refresh.contains("refresh_token=ms-refresh-1"));
+    assert_eq!(creds.get(&format!("refresh.{UUID}")).unwrap().as_deref(), Some("ms-refresh-2"));
+    assert_eq!(mock.hits(PROFILE).len(), 2);
+}
+
+#[tokio::test]
+async fn pending_and_slow_down_before_success() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .reset(TOKEN, 400, json!({ "error": "authorization_pending" }))
+        .on(TOKEN, 400, json!({ "error": "slow_down" }))
+        .on(TOKEN, 200, json!({
+            "access_token": "ms-access",
+            "refresh_token": "ms-refresh-1",
+            "expires_in": 3600,
+            "token_type": "Bearer"
+        }));
+    let h = Harness::new(&mock.serve().await);
+
+    // slow_down ajoute `slow_down_secs` (0 ici) à l'intervalle du poll suivant.
+    let started = std::time::Instant::now();
+    let account = h.device_login().await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert_eq!(account.uuid, UUID);
+    assert_eq!(h.statuses(), ["pending", "slow_down", "success"]);
+    let events = h.events.lock().unwrap();
+    let slow_down = events.iter().find(|(_, p)| p["status"] == "slow_down").unwrap();
+    assert_eq!(slow_down.1["interval"], h.ctx.config.slow_down_secs);
+    assert_eq!(slow_down.1["poll_id"], "poll-1");
+}
+
+#[tokio::test]
+async fn expired_device_code() {
+    let mock = Mock::default();
+    mock.happy_path().reset(TOKEN, 400, json!({ "error": "expired_token" }));
+    let h = Harness::new(&mock.serve().await);
+
+    assert_eq!(h.device_login().await.unwrap_err(), AuthError::DeviceCodeExpired);
+    assert_eq!(h.statuses(), ["expired"]);
+    assert!(mock.hits(XBL).is_empty());
+    assert_eq!(h.ctx.credentials.get("refresh.login.poll-1").unwrap(), None);
+}
+
+#[tokio::test]
+async fn xbox_errors_map_to_variants() {
+    let cases = [
+        (XBL, 2148916233u64, AuthError::NoXboxAccount),
+        (XSTS, 2148916238, AuthError::ChildAccount),
+        (XSTS, 2148916235, AuthError::RegionUnavailable),
+    ];
+    for (path, xerr, expected) in cases {
+        let mock = Mock::default();
+        mock.happy_path()
+            .reset(path, 401, json!({ "Identity": "0", "XErr": xerr, "Message": "", "Redirect": "https://start.ui.xboxlive.com" }));
+        let h = Harness::new(&mock.serve().await);
+
+        assert_eq!(h.device_login().await.unwrap_err(), expected);
+        // Le compte n'est pas identifié : rien n'est gardé.
+        assert_eq!(h.ctx.credentials.get("refresh.login.poll-1").unwrap(), None);
+        assert!(!h.ctx.accounts_file.exists());
+        assert!(mock.hits(MC_LOGIN).is_empty());
+    }
+}
+
+#[tokio::test]
+async fn empty_entitlements() {
+    let mock = Mock::default();
+    mock.happy_path().reset(ENTITLEMENTS, 200, json!({ "items": [] }));
+    let h = Harness::new(&mock.serve().await);
+
+    let err = h.device_login().await.unwrap_err();
+    assert_eq!(err, AuthError::NoEntitlement);
+    assert_eq!(err.code(), "no_entitlement");
+    assert!(mock.hits(PROFILE).is_empty());
+}
+
+#[tokio::test]
+async fn revoked_refresh_token_flags_account_for_relogin() {
+    let mock = Mock::default();
+    mock.happy_path();
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    // Redémarrage : plus de jetons en cache, et le refresh token a été révoqué entre-temps.
+    mock.reset(TOKEN, 400, json!({ "error": "invalid_grant" }));
+    let restarted = AuthContext { state: AuthState::default(), ..h.ctx.clone() };
+    assert_eq!(auth::fetch_profile(&restarted).await.unwrap_err(), AuthError::InvalidGrant);
+
+    assert_eq!(h.ctx.credentials.get(&format!("refresh.{UUID}")).unwrap(), None);
+    let index = AccountIndex::load(&h.ctx.accounts_file).unwrap();
+    let account = &index.summaries()[0];
+    assert_eq!(account.uuid, UUID);
+    assert!(account.needs_relogin);
+    let relogin = h.events.lock().unwrap().iter().find(|(event, _)| event == "auth://relogin").cloned();
+    assert_eq!(relogin.unwrap().1, json!({ "reason": "invalid_grant", "account": UUID }));
+
+    // Une nouvelle connexion lève la marque.
+    mock.reset(TOKEN, 200, json!({
+        "access_token": "ms-access-2",
+        "refresh_token": "ms-refresh-2",
+        "expires_in": 3600,
+        "token_type": "Bearer"
+    }));
+    assert!(!h.device_login().await.unwrap().needs_relogin);
+}
+
+#[tokio::test]
+async fn missing_profile_then_create_it() {
+    let created = json!({ "id": UUID, "name": "NewPlayer", "skins": [], "capes": [] });
+    let mock = Mock::default();
+    mock.happy_path()
+        .reset(PROFILE, 404, json!({ "path": "/minecraft/profile", "error": "NOT_FOUND" }))
+        .on(PROFILE, 400, json!({ "path": "/minecraft/profile", "details": { "status": "DUPLICATE" } }))
+        .on(PROFILE, 200, created.clone())
+        .on("/mc/minecraft/profile/name/NewPlayer/available", 200, json!({ "status": "AVAILABLE" }));
+    let h = Harness::new(&mock.serve().await);
+
+    // Jeu acheté sans profil : état dédié, la connexion reste en attente.
+    assert_eq!(h.device_login().await.unwrap_err(), AuthError::NoProfile);
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap().as_deref(), Some("ms-refresh-1"));
+    assert_eq!(h.ctx.credentials.get("refresh.login.poll-1").unwrap(), None);
+    assert_eq!(auth::fetch_profile(&h.ctx).await.unwrap_err(), AuthError::NotSignedIn);
+
+    // Pseudo vérifié avec le jeton de la connexion en attente.
+    assert_eq!(
+        profile::name_available(&h.ctx, "NewPlayer").await.unwrap(),
+        profile::NameAvailability::Available
+    );
+    assert_eq!(auth::create_profile(&h.ctx, "x").await.unwrap_err().code(), "invalid_name");
+    assert_eq!(
+        auth::create_profile(&h.ctx, "Notch").await.unwrap_err(),
+        AuthError::NameUnavailable("Notch".into())
+    );
+
+    let account = auth::create_profile(&h.ctx, "NewPlayer").await.unwrap();
+    assert_eq!(account.name, "NewPlayer");
+    assert!(account.active);
+    let creation = &mock.hits(PROFILE)[2];
+    assert_eq!(serde_json::from_str::<serde_json::Value>(creation).unwrap(), json!({ "profileName": "NewPlayer" }));
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap(), None);
+    assert_eq!(h.ctx.credentials.get(&format!("refresh.{UUID}")).unwrap().as_deref(), Some("ms-refresh-1"));
+    // Une seule connexion Xbox → Minecraft pour tout le parcours.
+    assert_eq!(mock.hits(MC_LOGIN).len(), 1);
+    assert_eq!(auth::fetch_profile(&h.ctx).await.unwrap().name, "NewPlayer");
+}
+
+#[tokio::test]
+async fn abandoned_profile_setup_is_discarded() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .on("/mc/minecraft/profile/name/NewPlayer/available", 200, json!({ "status": "AVAILABLE" }));
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    // Second compte, sans profil Java : création de profil en attente.
+    mock.reset(PROFILE, 404, json!({ "path": "/minecraft/profile", "error": "NOT_FOUND" }))
+        .reset(TOKEN, 200, json!({
+            "access_token": "ms-access-2",
+            "refresh_token": "ms-refresh-2",
+            "expires_in": 3600,
+            "token_type": "Bearer"
+        }));
+    assert_eq!(h.device_login().await.unwrap_err(), AuthError::NoProfile);
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap().as_deref(), Some("ms-refresh-2"));
+
+    // Redémarrage : l'entrée en attente est périmée, le compte actif garde son propre jeton.
+    let restarted = AuthContext { state: AuthState::default(), ..h.ctx.clone() };
+    profile::name_available(&restarted, "NewPlayer").await.unwrap();
+    assert!(mock.hits(TOKEN).last().unwrap().contains("refresh_token=ms-refresh-1"));
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap(), None);
+
+    // Abandon explicite.
+    assert_eq!(h.device_login().await.unwrap_err(), AuthError::NoProfile);
+    auth::cancel_profile_setup(&h.ctx).await;
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap(), None);
+
+    // Une nouvelle connexion remplace celle en attente.
+    assert_eq!(h.device_login().await.unwrap_err(), AuthError::NoProfile);
+    mock.reset(PROFILE, 200, json!({ "id": UUID, "name": "Kashir", "skins": [] }));
+    h.device_login().await.unwrap();
+    assert_eq!(h.ctx.credentials.get("refresh.pending").unwrap(), None);
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use kashirlauncher_lib::auth_config::{AuthConfig, CONFIG_FILE};
+
+#[test]
+fn defaults_without_file_or_env() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = AuthConfig::from_sources(&dir.path().join(CONFIG_FILE), |_| None).unwrap();
+    assert_eq!(cfg, AuthConfig::default());
+    assert_eq!(cfg.token_url(), "https://login.microsoftonline.com/consumers/oauth2/v2.0/token");
+    assert_eq!(cfg.profile_url(), "https://api.minecraftservices.com/minecraft/profile");
+    // RFC 8628 : +5 s par `slow_down`.
+    assert_eq!(cfg.slow_down_secs, 5);
+}
+
+#[test]
+fn env_overrides_file_overrides_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join(CONFIG_FILE);
+    std::fs::write(
+        &file,
+        r#"{ "auth": { "client_id": "from-file", "xbl_base": "http://127.0.0.1:9000/", "xsts_base": "http://127.0.0.1:9000" } }"#,
+    )
+    .unwrap();
+    let env: HashMap<&str, &str> = HashMap::from([
+        ("KASHIR_MS_CLIENT_ID", "from-env"),
+        ("KASHIR_MC_SERVICES_BASE", "http://127.0.0.1:9001"),
+    ]);
+    let cfg = AuthConfig::from_sources(&file, |name| env.get(name).map(|v| v.to_string())).unwrap();
+
+    assert_eq!(cfg.client_id, "from-env");
+    assert_eq!(cfg.xbl_url(), "http://127.0.0.1:9000/user/authenticate");
+    assert_eq!(cfg.xsts_url(), "http://127.0.0.1:9000/xsts/authorize");
+    assert_eq!(cfg.mc_login_url(), "http://127.0.0.1:9001/authentication/login_with_xbox");
+    assert_eq!(cfg.ms_login_base, AuthConfig::default().ms_login_base);
+}
+
+#[test]
+fn invalid_url_or_file_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join(CONFIG_FILE);
+    assert!(AuthConfig::from_sources(&file, |n| (n == "KASHIR_XBL_BASE").then(|| "pas une url".into())).is_err());
+
+    std::fs::write(&file, "{ not json").unwrap();
+    assert!(AuthConfig::from_sources(&file, |_| None).is_err());
+}
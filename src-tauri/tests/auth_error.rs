@@ -0,0 +1,30 @@
+use kashirlauncher_lib::auth_error::AuthError;
+
+#[test]
+fn xerr_codes_map_to_dedicated_variants() {
+    let cases = [
+        (2148916233, "no_xbox_account"),
+        (2148916227, "banned"),
+        (2148916238, "child_account"),
+        (2148916235, "region_unavailable"),
+        (2148916236, "region_unavailable"),
+        (2148916237, "region_unavailable"),
+        (2148916234, "xbox_error"),
+    ];
+    for (xerr, code) in cases {
+        assert_eq!(AuthError::from_xerr(xerr, String::new()).code(), code, "XErr {xerr}");
+    }
+}
+
+#[test]
+fn serialized_shape_is_code_messages_detail() {
+    let json = serde_json::to_value(AuthError::NoProfile).unwrap();
+    assert_eq!(json["code"], "no_profile");
+    assert!(json["message"]["fr"].as_str().unwrap().contains("profil Java"));
+    assert!(json["message"]["en"].as_str().unwrap().contains("Java profile"));
+    assert!(json["detail"].is_null());
+
+    let json = serde_json::to_value(AuthError::from_xerr(2148916234, "terms".into())).unwrap();
+    assert_eq!(json["code"], "xbox_error");
+    assert_eq!(json["detail"], "XErr=2148916234 – terms");
+}
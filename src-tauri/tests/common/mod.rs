@@ -0,0 +1,218 @@
+//! Faux services Microsoft / Xbox / Minecraft et contexte d'authentification en mémoire,
+//! partagés par les tests d'intégration.
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use kashirlauncher_lib::accounts::AccountSummary;
+use kashirlauncher_lib::auth::{self, AuthContext, AuthState, DevicePollArgs};
+use kashirlauncher_lib::auth_config::AuthConfig;
+use kashirlauncher_lib::auth_error::AuthError;
+use kashirlauncher_lib::credentials::{Credentials, MemoryStore};
+use kashirlauncher_lib::CancelToken;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+pub const DEVICE_CODE: &str = "/ms/devicecode";
+pub const TOKEN: &str = "/ms/token";
+pub const XBL: &str = "/xbl/user/authenticate";
+pub const XSTS: &str = "/xsts/xsts/authorize";
+pub const MC_LOGIN: &str = "/mc/authentication/login_with_xbox";
+pub const ENTITLEMENTS: &str = "/mc/entitlements/mcstore";
+pub const PROFILE: &str = "/mc/minecraft/profile";
+pub const SKINS: &str = "/mc/minecraft/profile/skins";
+pub const ACTIVE_SKIN: &str = "/mc/minecraft/profile/skins/active";
+pub const ACTIVE_CAPE: &str = "/mc/minecraft/profile/capes/active";
+
+pub const UUID: &str = "069a79f444e94726a5befca90e38aaf5";
+
+/* ===================== Faux services Microsoft / Xbox / Minecraft ===================== */
+
+type Script = HashMap<&'static str, VecDeque<(u16, Vec<u8>)>>;
+
+/// Réponses scriptées par chemin : la file est consommée dans l'ordre, la dernière réponse est
+/// rejouée indéfiniment. Chaque requête reçue est journalisée (chemin, corps).
+#[derive(Clone, Default)]
+pub struct Mock {
+    routes: Arc<Mutex<Script>>,
+    hits: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl Mock {
+    pub fn on(&self, path: &'static str, status: u16, body: Value) -> &Self {
+        self.on_bytes(path, status, body.to_string().into_bytes())
+    }
+
+    pub fn on_bytes(&self, path: &'static str, status: u16, body: Vec<u8>) -> &Self {
+        self.routes.lock().unwrap().entry(path).or_default().push_back((status, body));
+        self
+    }
+
+    /// Remplace les réponses déjà scriptées pour `path`.
+    pub fn reset(&self, path: &'static str, status: u16, body: Value) -> &Self {
+        self.routes.lock().unwrap().remove(path);
+        self.on(path, status, body)
+    }
+
+    fn reply(&self, path: &str) -> (u16, Vec<u8>) {
+        let mut routes = self.routes.lock().unwrap();
+        match routes.get_mut(path) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) => queue.front().cloned().unwrap(),
+            None => (404, Vec::new()),
+        }
+    }
+
+    pub fn hits(&self, path: &str) -> Vec<String> {
+        self.hits.lock().unwrap().iter().filter(|(p, _)| p == path).map(|(_, b)| b.clone()).collect()
+    }
+
+    /// Nombre de requêtes reçues, tous chemins confondus.
+    pub fn total_hits(&self) -> usize {
+        self.hits.lock().unwrap().len()
+    }
+
+    /// Démarre le serveur sur un port libre et renvoie son URL de base.
+    pub async fn serve(&self) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let mock = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mock = mock.clone();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let target = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    let path = target.split('?').next().unwrap().to_string();
+                    let mut len = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).await.unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                len = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+                    let mut body = vec![0u8; len];
+                    reader.read_exact(&mut body).await.unwrap();
+                    mock.hits.lock().unwrap().push((path.clone(), String::from_utf8_lossy(&body).into_owned()));
+
+                    let (status, body) = mock.reply(&path);
+                    let head = format!(
+                        "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    let _ = reader.get_mut().write_all(head.as_bytes()).await;
+                    let _ = reader.get_mut().write_all(&body).await;
+                    let _ = reader.get_mut().shutdown().await;
+                });
+            }
+        });
+        base
+    }
+
+    /// Chaîne complète qui aboutit : device code, jetons, Xbox, licence et profil.
+    pub fn happy_path(&self) -> &Self {
+        self.on(DEVICE_CODE, 200, json!({
+            "user_code": "ABCD-EFGH",
+            "device_code": "dc-1",
+            "verification_uri": "https://microsoft.com/link",
+            "expires_in": 900,
+            "interval": 5,
+            "message": "Rendez-vous sur https://microsoft.com/link"
+        }))
+        .on(TOKEN, 200, json!({
+            "access_token": "ms-access",
+            "refresh_token": "ms-refresh-1",
+            "expires_in": 3600,
+            "token_type": "Bearer"
+        }))
+        .on(XBL, 200, json!({
+            "Token": "xbl-token",
+            "NotAfter": "2099-01-01T00:00:00.0000000Z",
+            "DisplayClaims": { "xui": [{ "uhs": "uhs-1" }] }
+        }))
+        .on(XSTS, 200, json!({
+            "Token": "xsts-token",
+            "NotAfter": "2099-01-01T00:00:00.0000000Z",
+            "DisplayClaims": { "xui": [{ "uhs": "uhs-1" }] }
+        }))
+        .on(MC_LOGIN, 200, json!({ "access_token": "mc-access", "expires_in": 86400, "token_type": "Bearer" }))
+        .on(ENTITLEMENTS, 200, json!({ "items": [{ "name": "game_minecraft" }, { "name": "product_minecraft" }] }))
+        .on(PROFILE, 200, json!({
+            "id": UUID,
+            "name": "Kashir",
+            "skins": [{ "url": "https://textures.minecraft.net/texture/abc", "state": "ACTIVE" }]
+        }))
+    }
+}
+
+/* ===================== Contexte de test ===================== */
+
+pub struct Harness {
+    pub ctx: AuthContext,
+    pub events: Arc<Mutex<Vec<(String, Value)>>>,
+    _dir: tempfile::TempDir,
+}
+
+impl Harness {
+    pub fn new(base: &str) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = AuthContext {
+            credentials: Credentials::new(Arc::new(MemoryStore::default())),
+            config: AuthConfig {
+                client_id: "test-client".into(),
+                ms_login_base: format!("{base}/ms"),
+                xbl_base: format!("{base}/xbl"),
+                xsts_base: format!("{base}/xsts"),
+                mc_services_base: format!("{base}/mc"),
+                // Pas de vraie attente après `slow_down`.
+                slow_down_secs: 0,
+            },
+            accounts_file: dir.path().join("accounts.json"),
+            state: AuthState::default(),
+            http: reqwest::Client::new(),
+            events: Arc::new(move |event, payload| sink.lock().unwrap().push((event.to_string(), payload))),
+        };
+        Self { ctx, events, _dir: dir }
+    }
+
+    pub fn statuses(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(event, _)| event == "auth://status")
+            .map(|(_, payload)| payload["status"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    /// `mc_ms_start_device_code` puis `mc_ms_poll_and_store`, sans attente entre deux polls.
+    pub async fn device_login(&self) -> Result<AccountSummary, AuthError> {
+        let config = self.ctx.config.clone();
+        let start = tokio::task::spawn_blocking(move || auth::request_device_code(&config, "poll-1".into()))
+            .await
+            .unwrap()?;
+        let args = DevicePollArgs {
+            poll_id: start.poll_id,
+            device_code: start.device_code,
+            interval_secs: 0,
+            timeout_secs: 60,
+        };
+        auth::poll_and_store(&self.ctx, &CancelToken::default(), &args).await
+    }
+}
+
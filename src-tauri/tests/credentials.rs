@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use kashirlauncher_lib::credentials::{CredentialStore, Credentials, EncryptedFileStore, MemoryStore};
+
+/// Contrat commun à tous les coffres.
+fn exercise(store: &dyn CredentialStore) {
+    assert_eq!(store.get("ksrefresh").unwrap(), None);
+    store.delete("ksrefresh").unwrap();
+
+    store.set("ksrefresh", "M.R3_BAY.token-1").unwrap();
+    assert_eq!(store.get("ksrefresh").unwrap().as_deref(), Some("M.R3_BAY.token-1"));
+
+    // Rotation : la nouvelle valeur remplace l'ancienne.
+    store.set("ksrefresh", "M.R3_BAY.token-2").unwrap();
+    assert_eq!(store.get("ksrefresh").unwrap().as_deref(), Some("M.R3_BAY.token-2"));
+
+    store.set("other", "x").unwrap();
+    store.delete("ksrefresh").unwrap();
+    assert_eq!(store.get("ksrefresh").unwrap(), None);
+    assert_eq!(store.get("other").unwrap().as_deref(), Some("x"));
+}
+
+#[test]
+fn memory_store_contract() {
+    exercise(&MemoryStore::default());
+}
+
+#[test]
+fn status_reports_the_fallback_reason() {
+    let native = Credentials::new(Arc::new(MemoryStore::default()));
+    let status = native.status();
+    assert_eq!((status.store, status.fallback), ("memory", None));
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = EncryptedFileStore::new(dir.path().to_path_buf(), &[7u8; 32]);
+    let reason = "trousseau keyring indisponible : DBus error".to_string();
+    let fallback = Credentials::with_fallback(Arc::new(file), reason.clone());
+    assert_eq!(fallback.status().fallback, Some(reason));
+    fallback.set("ksrefresh", "abc").unwrap();
+    assert_eq!(fallback.get("ksrefresh").unwrap().as_deref(), Some("abc"));
+}
+
+#[test]
+fn encrypted_file_store_contract() {
+    let dir = tempfile::tempdir().unwrap();
+    exercise(&EncryptedFileStore::new(dir.path().to_path_buf(), &[7u8; 32]));
+}
+
+#[test]
+fn encrypted_file_is_not_plaintext_and_needs_the_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = EncryptedFileStore::new(dir.path().to_path_buf(), &[1u8; 32]);
+    store.set("ksrefresh", "super-secret-refresh").unwrap();
+
+    let raw = std::fs::read(dir.path().join("ksrefresh.bin")).unwrap();
+    assert!(!raw.windows(12).any(|w| w == b"super-secret"));
+
+    let other = EncryptedFileStore::new(dir.path().to_path_buf(), &[2u8; 32]);
+    assert!(other.get("ksrefresh").is_err());
+}
+
+#[test]
+fn encrypted_file_store_from_device_secret() {
+    let dir = tempfile::tempdir().unwrap();
+    // Même encodage que `security::device_secret` : 32 octets en base64 sans padding.
+    let secret = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8";
+    let store = EncryptedFileStore::from_device_secret(dir.path().to_path_buf(), secret).unwrap();
+    store.set("ksrefresh", "abc").unwrap();
+    let again = EncryptedFileStore::from_device_secret(dir.path().to_path_buf(), secret).unwrap();
+    assert_eq!(again.get("ksrefresh").unwrap().as_deref(), Some("abc"));
+
+    assert!(EncryptedFileStore::from_device_secret(dir.path().to_path_buf(), "dG9vLXNob3J0").is_err());
+}
+
+#[test]
+fn encrypted_file_store_rejects_path_like_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = EncryptedFileStore::new(dir.path().to_path_buf(), &[0u8; 32]);
+    assert!(store.set("../escape", "x").is_err());
+    assert!(store.get("a/b").is_err());
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use kashirlauncher_lib::download::{self, Downloader, FileTask, ProgressFn};
+use kashirlauncher_lib::McError;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+/* ===================== Serveur de fichiers local ===================== */
+
+/// (chemin, en-tête `Range`) de chaque requête reçue.
+type Requests = Vec<(String, Option<String>)>;
+
+/// Sert des fixtures en mémoire : `Range: bytes=N-` honoré (206), pannes scriptées par chemin,
+/// corps éventuellement retenu à mi-chemin. Chaque requête est journalisée avec son en-tête `Range`.
+#[derive(Clone, Default)]
+struct Files {
+    files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    failures: Arc<Mutex<HashMap<String, VecDeque<u16>>>>,
+    gates: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+    requests: Arc<Mutex<Requests>>,
+}
+
+impl Files {
+    fn put(&self, path: &str, data: Vec<u8>) {
+        self.files.lock().unwrap().insert(path.into(), data);
+    }
+
+    /// Les prochaines requêtes sur `path` reçoivent `status`, dans l'ordre des appels.
+    fn fail(&self, path: &str, status: u16) {
+        self.failures.lock().unwrap().entry(path.into()).or_default().push_back(status);
+    }
+
+    /// Envoie la première moitié du corps puis attend `notify_one` pour la suite.
+    fn gate(&self, path: &str) -> Arc<Notify> {
+        let notify = Arc::new(Notify::new());
+        self.gates.lock().unwrap().insert(path.into(), notify.clone());
+        notify
+    }
+
+    /// En-têtes `Range` des requêtes reçues pour `path`.
+    fn ranges(&self, path: &str) -> Vec<Option<String>> {
+        self.requests.lock().unwrap().iter().filter(|(p, _)| p == path).map(|(_, r)| r.clone()).collect()
+    }
+
+    async fn serve(&self) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let files = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let files = files.clone();
+                tokio::spawn(async move { files.handle(stream).await });
+            }
+        });
+        base
+    }
+
+    async fn handle(&self, stream: tokio::net::TcpStream) {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+            return;
+        }
+        let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+        let mut range = None;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).await.unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_string());
+                }
+            }
+        }
+        self.requests.lock().unwrap().push((path.clone(), range.clone()));
+
+        let failure = self.failures.lock().unwrap().get_mut(&path).and_then(VecDeque::pop_front);
+        let data = self.files.lock().unwrap().get(&path).cloned();
+        let (status, extra, body) = match (failure, data) {
+            (Some(status), _) => (status, String::new(), Vec::new()),
+            (None, None) => (404, String::new(), Vec::new()),
+            (None, Some(data)) => {
+                let start = range
+                    .as_deref()
+                    .and_then(|r| r.strip_prefix("bytes="))
+                    .and_then(|r| r.strip_suffix('-'))
+                    .and_then(|n| n.parse::<usize>().ok());
+                match start {
+                    Some(start) => {
+                        let extra = format!("Content-Range: bytes {start}-{}/{}\r\n", data.len() - 1, data.len());
+                        (206, extra, data[start..].to_vec())
+                    }
+                    None => (200, String::new(), data),
+                }
+            }
+        };
+
+        let stream = reader.get_mut();
+        let head = format!(
+            "HTTP/1.1 {status} X\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n{extra}Connection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(head.as_bytes()).await;
+        let gate = self.gates.lock().unwrap().get(&path).cloned();
+        let (first, rest) = match gate {
+            Some(_) => body.split_at(body.len() / 2),
+            None => (&body[..], &[][..]),
+        };
+        let _ = stream.write_all(first).await;
+        let _ = stream.flush().await;
+        if let Some(gate) = gate {
+            gate.notified().await;
+        }
+        let _ = stream.write_all(rest).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+/* ===================== Helpers ===================== */
+
+/// Contenu déterministe non trivial.
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn task(base: &str, url_path: &str, path: &Path, data: &[u8]) -> FileTask {
+    FileTask {
+        url: format!("{base}{url_path}"),
+        path: path.to_path_buf(),
+        sha1: download::sha1_hex(data),
+        size: data.len() as u64,
+    }
+}
+
+/// Retries quasi immédiats pour ne pas attendre le backoff de production.
+fn downloader(max_retries: u32) -> Downloader {
+    Downloader::new()
+        .unwrap()
+        .with_concurrency(2)
+        .with_max_retries(max_retries)
+        .with_base_delay(Duration::from_millis(1))
+}
+
+/// Fonction de progression qui garde le dernier `(fait, total)`.
+fn progress() -> (ProgressFn, Arc<Mutex<(u64, u64)>>) {
+    let last = Arc::new(Mutex::new((0, 0)));
+    let sink = last.clone();
+    (Arc::new(move |done, total| *sink.lock().unwrap() = (done, total)), last)
+}
+
+/* ===================== Scénarios ===================== */
+
+#[tokio::test]
+async fn resumes_part_file_with_range() {
+    let files = Files::default();
+    let data = payload(200_000);
+    files.put("/lib.jar", data.clone());
+    let base = files.serve().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("libraries/lib.jar");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(download::part_path(&path), &data[..70_000]).unwrap();
+
+    let (on_progress, last) = progress();
+    downloader(0).download_all(vec![task(&base, "/lib.jar", &path, &data)], on_progress).await.unwrap();
+
+    assert_eq!(files.ranges("/lib.jar"), [Some("bytes=70000-".to_string())]);
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+    assert!(!download::part_path(&path).exists());
+    assert_eq!(*last.lock().unwrap(), (200_000, 200_000));
+}
+
+#[tokio::test]
+async fn retries_server_errors_then_succeeds() {
+    let files = Files::default();
+    let data = payload(4_096);
+    files.put("/asset", data.clone());
+    files.fail("/asset", 503);
+    files.fail("/asset", 500);
+    let base = files.serve().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("asset");
+
+    let (on_progress, last) = progress();
+    downloader(2).download_all(vec![task(&base, "/asset", &path, &data)], on_progress).await.unwrap();
+
+    assert_eq!(files.ranges("/asset").len(), 3);
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+    // Les tentatives ratées ne comptent pas deux fois dans la progression.
+    assert_eq!(*last.lock().unwrap(), (4_096, 4_096));
+
+    // `fetch` suit la même politique.
+    files.fail("/asset", 502);
+    assert_eq!(downloader(1).fetch(&format!("{base}/asset")).await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn not_found_is_fatal() {
+    let files = Files::default();
+    let base = files.serve().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.jar");
+
+    let (on_progress, _) = progress();
+    let err = downloader(3)
+        .download_all(vec![task(&base, "/missing.jar", &path, b"x")], on_progress)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(&err, McError::Http(msg) if msg.contains("404")), "{err}");
+    assert_eq!(files.ranges("/missing.jar").len(), 1);
+    assert!(!path.exists());
+    assert!(!download::part_path(&path).exists());
+}
+
+#[tokio::test]
+async fn checksum_or_size_mismatch_leaves_no_file() {
+    let files = Files::default();
+    let expected = payload(1_000);
+    let mut corrupted = expected.clone();
+    corrupted[500] ^= 0xff;
+    files.put("/corrupted", corrupted);
+    files.put("/truncated", expected[..900].to_vec());
+    let base = files.serve().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    for name in ["/corrupted", "/truncated"] {
+        let path = dir.path().join(&name[1..]);
+        let (on_progress, _) = progress();
+        let err = downloader(1)
+            .download_all(vec![task(&base, name, &path, &expected)], on_progress)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, McError::Integrity(_)), "{name}: {err}");
+        // Corruption = erreur transitoire : une nouvelle tentative, sans reprise du `.part` jeté.
+        assert_eq!(files.ranges(name), [None, None], "{name}");
+        assert!(!path.exists(), "{name}");
+        assert!(!download::part_path(&path).exists(), "{name}");
+    }
+}
+
+#[tokio::test]
+async fn final_file_replaced_only_once_validated() {
+    let files = Files::default();
+    let data = payload(64 * 1024);
+    files.put("/client.jar", data.clone());
+    let gate = files.gate("/client.jar");
+    let base = files.serve().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("client.jar");
+    let part = download::part_path(&path);
+    std::fs::write(&path, b"ancienne version corrompue").unwrap();
+
+    let (on_progress, _) = progress();
+    let job = tokio::spawn({
+        let task = task(&base, "/client.jar", &path, &data);
+        async move { downloader(0).download_all(vec![task], on_progress).await }
+    });
+
+    // Moitié reçue : tout est dans le `.part`, l'ancien fichier n'a pas bougé.
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while std::fs::metadata(&part).map(|m| m.len()).unwrap_or(0) < 32 * 1024 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"ancienne version corrompue");
+
+    gate.notify_one();
+    job.await.unwrap().unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+    assert!(!part.exists());
+
+    // Fichier déjà valide : aucune requête.
+    let (on_progress, last) = progress();
+    downloader(0).download_all(vec![task(&base, "/client.jar", &path, &data)], on_progress).await.unwrap();
+    assert_eq!(files.ranges("/client.jar").len(), 1);
+    assert_eq!(*last.lock().unwrap(), (64 * 1024, 64 * 1024));
+}
@@ -0,0 +1,37 @@
+use kashirlauncher_lib::download::Downloader;
+use kashirlauncher_lib::install::{self, GameDir};
+use kashirlauncher_lib::{CancelToken, McError};
+
+/// Downloader déjà annulé : toute requête échoue, comme hors ligne.
+fn offline() -> Downloader {
+    let cancel = CancelToken::default();
+    cancel.cancel();
+    Downloader::new().unwrap().with_cancel(cancel)
+}
+
+#[tokio::test]
+async fn installed_version_resolves_without_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    let dl = offline();
+
+    // Rien sur disque : le manifeste est nécessaire.
+    let err = install::resolve_version(&dl, &dirs, Some("1.20.4")).await.unwrap_err();
+    assert!(matches!(err, McError::Cancelled), "{err}");
+
+    let path = dirs.version_json("1.20.4");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, include_str!("fixtures/1.20.4.json")).unwrap();
+    let version = install::resolve_version(&dl, &dirs, Some("1.20.4")).await.unwrap();
+    assert_eq!(version.id, "1.20.4");
+    assert_eq!(version.java_version.unwrap().major_version, 17);
+
+    // « Dernière release » : seule la liste en ligne la connaît.
+    let err = install::resolve_version(&dl, &dirs, None).await.unwrap_err();
+    assert!(matches!(err, McError::Cancelled), "{err}");
+
+    // JSON local illisible : retour au manifeste.
+    std::fs::write(&path, b"{ tronque").unwrap();
+    let err = install::resolve_version(&dl, &dirs, Some("1.20.4")).await.unwrap_err();
+    assert!(matches!(err, McError::Cancelled), "{err}");
+}
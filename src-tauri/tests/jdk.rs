@@ -0,0 +1,162 @@
+use kashirlauncher_lib::install::GameDir;
+use kashirlauncher_lib::jdk::{self, JavaProps};
+use kashirlauncher_lib::rules::{Arch, OsName, Platform};
+
+fn platform(arch: Arch) -> Platform {
+    Platform { os: OsName::Linux, arch, os_version: "6.8.0".into() }
+}
+
+/// `release` d'un JDK Temurin 17 (extrait).
+const TEMURIN_17_RELEASE: &str = r#"IMPLEMENTOR="Eclipse Adoptium"
+IMPLEMENTOR_VERSION="Temurin-17.0.10+7"
+JAVA_RUNTIME_VERSION="17.0.10+7"
+JAVA_VERSION="17.0.10"
+JAVA_VERSION_DATE="2024-01-16"
+LIBC="gnu"
+MODULES="java.base java.compiler java.datatransfer"
+OS_ARCH="x86_64"
+OS_NAME="Linux"
+SOURCE=".:git:6a4ee3f5f23c"
+"#;
+
+/// `release` d'un JRE 8 : préfixe `1.` et arch `amd64`.
+const JRE_8_RELEASE: &str = r#"JAVA_VERSION="1.8.0_392"
+OS_NAME="Windows"
+OS_VERSION="5.2"
+OS_ARCH="amd64"
+SOURCE=".:git:7e2d1a2f3f4c"
+IMPLEMENTOR="Eclipse Adoptium"
+"#;
+
+/// Stderr de `java -XshowSettings:properties -version` (OpenJDK 21, macOS arm64, extrait).
+const SHOW_SETTINGS_21: &str = "Property settings:
+    file.encoding = UTF-8
+    java.home = /Library/Java/JavaVirtualMachines/temurin-21.jdk/Contents/Home
+    java.class.path =
+    java.runtime.version = 21.0.2+13-LTS
+    java.specification.version = 21
+    java.vendor = Eclipse Adoptium
+    java.vendor.url = https://adoptium.net/
+    java.version = 21.0.2
+    java.version.date = 2024-01-16
+    os.arch = aarch64
+    os.name = Mac OS X
+    os.version = 14.2.1
+
+openjdk version \"21.0.2\" 2024-01-16 LTS
+OpenJDK Runtime Environment Temurin-21.0.2+13 (build 21.0.2+13-LTS)
+OpenJDK 64-Bit Server VM Temurin-21.0.2+13 (build 21.0.2+13-LTS, mixed mode)
+";
+
+/// Même sortie pour un Java 8 : `java.version = 1.8.0_392`.
+const SHOW_SETTINGS_8: &str = "Property settings:
+    awt.toolkit = sun.awt.X11.XToolkit
+    java.specification.version = 1.8
+    java.vendor = Temurin
+    java.version = 1.8.0_392
+    os.arch = amd64
+    os.name = Linux
+
+openjdk version \"1.8.0_392\"
+OpenJDK Runtime Environment (Temurin)(build 1.8.0_392-b08)
+";
+
+#[test]
+fn major_from_both_version_schemes() {
+    assert_eq!(jdk::parse_major("1.8.0_392"), Some(8));
+    assert_eq!(jdk::parse_major("1.7.0_80"), Some(7));
+    assert_eq!(jdk::parse_major("21.0.2"), Some(21));
+    assert_eq!(jdk::parse_major("17"), Some(17));
+    assert_eq!(jdk::parse_major("22-ea"), Some(22));
+    assert_eq!(jdk::parse_major(""), None);
+    assert_eq!(jdk::parse_major("1"), None);
+}
+
+#[test]
+fn arch_aliases() {
+    assert_eq!(jdk::parse_arch("amd64"), Some(Arch::X86_64));
+    assert_eq!(jdk::parse_arch("x86_64"), Some(Arch::X86_64));
+    assert_eq!(jdk::parse_arch(" AArch64 "), Some(Arch::Aarch64));
+    assert_eq!(jdk::parse_arch("i386"), Some(Arch::X86));
+    assert_eq!(jdk::parse_arch("ppc64le"), None);
+}
+
+#[test]
+fn release_file() {
+    assert_eq!(
+        jdk::parse_release_file(TEMURIN_17_RELEASE),
+        JavaProps {
+            version: Some("17.0.10".into()),
+            arch: Some("x86_64".into()),
+            vendor: Some("Eclipse Adoptium".into()),
+        }
+    );
+    let jre8 = jdk::parse_release_file(JRE_8_RELEASE);
+    assert_eq!(jre8.version.as_deref(), Some("1.8.0_392"));
+    assert_eq!(jre8.arch.as_deref(), Some("amd64"));
+    assert_eq!(jdk::parse_major(&jre8.version.unwrap()), Some(8));
+
+    assert_eq!(jdk::parse_release_file("MODULES=\"java.base\"\n"), JavaProps::default());
+}
+
+#[test]
+fn show_settings_output() {
+    assert_eq!(
+        jdk::parse_show_settings(SHOW_SETTINGS_21),
+        JavaProps {
+            version: Some("21.0.2".into()),
+            arch: Some("aarch64".into()),
+            vendor: Some("Eclipse Adoptium".into()),
+        }
+    );
+    // `java.version.date`, `java.vendor.url` : clés voisines, pas confondues.
+    let java8 = jdk::parse_show_settings(SHOW_SETTINGS_8);
+    assert_eq!(java8.version.as_deref(), Some("1.8.0_392"));
+    assert_eq!(java8.arch.as_deref(), Some("amd64"));
+    assert_eq!(java8.vendor.as_deref(), Some("Temurin"));
+
+    assert_eq!(jdk::parse_show_settings("Error: could not find libjava.so\n"), JavaProps::default());
+}
+
+#[test]
+fn compatibility_rules() {
+    let x64 = platform(Arch::X86_64);
+    // Java 8 exigé tel quel.
+    assert!(jdk::is_compatible(8, Some(Arch::X86_64), 8, &x64));
+    assert!(!jdk::is_compatible(17, Some(Arch::X86_64), 8, &x64));
+    assert!(!jdk::is_compatible(7, Some(Arch::X86_64), 8, &x64));
+    // Au-delà : plus récent accepté, plus ancien refusé.
+    assert!(jdk::is_compatible(21, Some(Arch::X86_64), 17, &x64));
+    assert!(jdk::is_compatible(17, Some(Arch::X86_64), 17, &x64));
+    assert!(!jdk::is_compatible(17, Some(Arch::X86_64), 21, &x64));
+    // Arch différente ou inconnue : refusé.
+    assert!(!jdk::is_compatible(21, Some(Arch::X86_64), 21, &platform(Arch::Aarch64)));
+    assert!(!jdk::is_compatible(21, None, 21, &x64));
+}
+
+#[test]
+fn managed_runtime_arch_mismatch_is_listed_but_incompatible() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    let home = dir.path().join("runtime/java-runtime-gamma");
+    std::fs::create_dir_all(home.join("bin")).unwrap();
+    std::fs::write(home.join("bin/java"), b"").unwrap();
+    std::fs::write(home.join("release"), TEMURIN_17_RELEASE).unwrap();
+
+    let managed = |arch| {
+        jdk::discover(&dirs, &platform(arch), 17)
+            .into_iter()
+            .filter(|j| j.managed)
+            .collect::<Vec<_>>()
+    };
+
+    let on_x64 = managed(Arch::X86_64);
+    assert_eq!(on_x64.len(), 1);
+    assert_eq!((on_x64[0].major, on_x64[0].arch.as_str()), (17, "x86_64"));
+    assert!(on_x64[0].compatible);
+
+    // Runtime x86_64 copié sur une machine arm64 : visible, jamais choisi.
+    let on_arm = managed(Arch::Aarch64);
+    assert_eq!(on_arm.len(), 1);
+    assert!(!on_arm[0].compatible);
+}
@@ -0,0 +1,82 @@
+use std::io::Write;
+use std::path::Path;
+
+use kashirlauncher_lib::install::GameDir;
+use kashirlauncher_lib::natives::{self, NativesDir};
+use kashirlauncher_lib::rules::{Arch, OsName, Platform};
+use kashirlauncher_lib::version::VersionJson;
+use zip::write::SimpleFileOptions;
+
+const LWJGL_LINUX: &str = "org/lwjgl/lwjgl/lwjgl-platform/2.9.0/lwjgl-platform-2.9.0-natives-linux.jar";
+
+fn linux() -> Platform {
+    Platform { os: OsName::Linux, arch: Arch::X86_64, os_version: "6.8.0".into() }
+}
+
+/// Jar de natives minimal : `(nom d'entrée, contenu)`, les noms finissant par `/` sont des dossiers.
+fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, data) in entries {
+        if name.ends_with('/') {
+            zip.add_directory(*name, SimpleFileOptions::default()).unwrap();
+        } else {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn extracts_natives_with_excludes_and_cleans_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().join("mc"));
+    let version: VersionJson = serde_json::from_str(include_str!("fixtures/1.7.2.json")).unwrap();
+    write_jar(
+        &dirs.libraries().join(LWJGL_LINUX),
+        &[
+            ("META-INF/", b""),
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n"),
+            ("META-INF/LWJGL.SF", b"signature"),
+            ("liblwjgl64.so", b"\x7fELF lwjgl"),
+            ("linux/libopenal64.so", b"\x7fELF openal"),
+            ("../evil.so", b"outside"),
+            ("/tmp/absolute.so", b"outside"),
+        ],
+    );
+
+    let natives_dir = NativesDir::create(&dirs, "1.7.2").unwrap();
+    let dest = natives_dir.path().to_path_buf();
+    assert!(dest.starts_with(dirs.root().join("natives")));
+    natives::extract_natives(&dirs, &version, &linux(), &dest).unwrap();
+
+    assert_eq!(std::fs::read(dest.join("liblwjgl64.so")).unwrap(), b"\x7fELF lwjgl");
+    assert_eq!(std::fs::read(dest.join("linux/libopenal64.so")).unwrap(), b"\x7fELF openal");
+    // `extract.exclude` : préfixe `META-INF/`.
+    assert!(!dest.join("META-INF").exists());
+    // Entrées qui sortiraient du dossier : ignorées.
+    assert!(!dest.parent().unwrap().join("evil.so").exists());
+    let mut names: Vec<_> = std::fs::read_dir(&dest)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["liblwjgl64.so", "linux"]);
+
+    // Fin du jeu : le dossier du lancement disparaît, le reste du dossier de jeu non.
+    drop(natives_dir);
+    assert!(!dest.exists());
+    assert!(dirs.libraries().join(LWJGL_LINUX).exists());
+}
+
+#[test]
+fn missing_native_jar_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    let version: VersionJson = serde_json::from_str(include_str!("fixtures/1.7.2.json")).unwrap();
+    let dest = dir.path().join("out");
+
+    let err = natives::extract_natives(&dirs, &version, &linux(), &dest).unwrap_err();
+    assert!(err.to_string().contains("lwjgl-platform-2.9.0-natives-linux.jar"), "{err}");
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use kashirlauncher_lib::auth_error::AuthError;
+use kashirlauncher_lib::oauth::{self, Loopback, Pkce};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+/// Faux endpoint `/token` : répond `status`/`body` à une requête et renvoie le formulaire reçu.
+async fn fake_token_endpoint(status: &'static str, body: &'static str) -> (String, oneshot::Receiver<HashMap<String, String>>) {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let url = format!("http://{}/token", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    len = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut form = vec![0u8; len];
+        reader.read_exact(&mut form).await.unwrap();
+        let form = reqwest::Url::parse(&format!("http://x/?{}", String::from_utf8(form).unwrap()))
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect();
+        let _ = tx.send(form);
+
+        let resp = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        reader.get_mut().write_all(resp.as_bytes()).await.unwrap();
+    });
+    (url, rx)
+}
+
+#[test]
+fn pkce_challenge_is_s256_of_verifier() {
+    // Exemple de l'annexe B de la RFC 7636.
+    let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".into());
+    assert_eq!(pkce.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+
+    let random = Pkce::generate().unwrap();
+    assert_eq!(random.verifier.len(), 43);
+}
+
+#[tokio::test]
+async fn browser_login_against_fake_endpoint() {
+    let pkce = Pkce::generate().unwrap();
+    let state = oauth::random_state();
+    let loopback = Loopback::bind().await.unwrap();
+    let redirect_uri = loopback.redirect_uri.clone();
+    assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+
+    let url = oauth::authorize_url("https://login.example/authorize", "client", "XboxLive.signin offline_access", &redirect_uri, &pkce, &state).unwrap();
+    let url = reqwest::Url::parse(&url).unwrap();
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(query["code_challenge"], pkce.challenge);
+    assert_eq!(query["code_challenge_method"], "S256");
+    assert_eq!(query["redirect_uri"], redirect_uri);
+
+    // Le « navigateur » : une requête parasite puis la redirection attendue.
+    let waiter = tokio::spawn(async move { loopback.wait_for_code(&state).await });
+    let client = reqwest::Client::new();
+    let favicon = client.get(format!("{redirect_uri}favicon.ico")).send().await.unwrap();
+    assert_eq!(favicon.status(), 404);
+    let forged = client.get(format!("{redirect_uri}?code=evil&state=wrong")).send().await.unwrap();
+    assert_eq!(forged.status(), 400);
+    let state = query["state"].clone();
+    let page = client
+        .get(format!("{redirect_uri}?code=the-code&state={state}"))
+        .send()
+        .await
+        .unwrap();
+    assert!(page.status().is_success());
+    let code = waiter.await.unwrap().unwrap();
+    assert_eq!(code, "the-code");
+
+    let (token_url, form) = fake_token_endpoint(
+        "200 OK",
+        r#"{"access_token":"at","refresh_token":"rt","expires_in":3600,"token_type":"Bearer"}"#,
+    )
+    .await;
+    let token = oauth::exchange_code(&client, &token_url, "client", "XboxLive.signin offline_access", &code, &redirect_uri, &pkce)
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "at");
+    assert_eq!(token.refresh_token.as_deref(), Some("rt"));
+
+    let form = form.await.unwrap();
+    assert_eq!(form["grant_type"], "authorization_code");
+    assert_eq!(form["code"], "the-code");
+    assert_eq!(form["code_verifier"], pkce.verifier);
+    assert_eq!(form["redirect_uri"], redirect_uri);
+}
+
+#[tokio::test]
+async fn idle_connection_does_not_block_redirect() {
+    let loopback = Loopback::bind().await.unwrap();
+    let redirect_uri = loopback.redirect_uri.clone();
+    let waiter = tokio::spawn(async move { loopback.wait_for_code("s").await });
+
+    // Socket préouverte par le navigateur, qui n'envoie jamais rien.
+    let addr = redirect_uri.trim_start_matches("http://").trim_end_matches('/').to_string();
+    let _idle = TcpStream::connect(&addr).await.unwrap();
+
+    let page = tokio::time::timeout(
+        Duration::from_secs(2),
+        reqwest::get(format!("{redirect_uri}?code=the-code&state=s")),
+    )
+    .await
+    .expect("redirection bloquée par la connexion muette")
+    .unwrap();
+    assert!(page.status().is_success());
+    assert_eq!(waiter.await.unwrap().unwrap(), "the-code");
+}
+
+#[tokio::test]
+async fn denied_consent_and_rejected_code() {
+    let loopback = Loopback::bind().await.unwrap();
+    let redirect_uri = loopback.redirect_uri.clone();
+    let waiter = tokio::spawn(async move { loopback.wait_for_code("s").await });
+    reqwest::get(format!("{redirect_uri}?error=access_denied&error_description=refus&state=s"))
+        .await
+        .unwrap();
+    let err = waiter.await.unwrap().unwrap_err();
+    assert_eq!(err, AuthError::Declined);
+
+    let (token_url, _) = fake_token_endpoint("400 Bad Request", r#"{"error":"invalid_grant"}"#).await;
+    let err = oauth::exchange_code(&reqwest::Client::new(), &token_url, "client", "scope", "code", &redirect_uri, &Pkce::generate().unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(err, AuthError::InvalidGrant);
+    assert_eq!(err.code(), "invalid_grant");
+}
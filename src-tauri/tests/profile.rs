@@ -0,0 +1,211 @@
+mod common;
+
+use common::*;
+use kashirlauncher_lib::auth::{AuthContext, AuthState};
+use kashirlauncher_lib::auth_error::AuthError;
+use kashirlauncher_lib::profile::{self, SkinVariant};
+use serde_json::json;
+
+/// PNG RGBA uni de `width`x`height`.
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&vec![0x7f; (width * height * 4) as usize]).unwrap();
+    writer.finish().unwrap();
+    out
+}
+
+fn profile_with_skin(url: &str, variant: &str) -> serde_json::Value {
+    json!({
+        "id": UUID,
+        "name": "Kashir",
+        "skins": [{ "id": "s1", "state": "ACTIVE", "url": url, "variant": variant.to_uppercase() }],
+        "capes": []
+    })
+}
+
+#[test]
+fn skin_png_validation() {
+    assert_eq!(profile::validate_skin_png(&png(64, 64)).unwrap(), (64, 64));
+    assert_eq!(profile::validate_skin_png(&png(64, 32)).unwrap(), (64, 32));
+
+    for (w, h) in [(128, 128), (32, 64), (64, 48)] {
+        assert!(matches!(profile::validate_skin_png(&png(w, h)), Err(AuthError::InvalidSkin(_))));
+    }
+    assert!(matches!(profile::validate_skin_png(b"GIF89a"), Err(AuthError::InvalidSkin(_))));
+    let truncated = png(64, 64);
+    let truncated = &truncated[..truncated.len() - 20];
+    assert!(matches!(profile::validate_skin_png(truncated), Err(AuthError::InvalidSkin(_))));
+}
+
+#[tokio::test]
+async fn upload_switch_and_reset_skin() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .on(SKINS, 200, profile_with_skin("https://textures.minecraft.net/texture/uploaded", "slim"))
+        .on(SKINS, 200, profile_with_skin("https://textures.minecraft.net/texture/linked", "classic"))
+        .on(ACTIVE_SKIN, 200, profile_with_skin("https://textures.minecraft.net/texture/steve", "classic"));
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    let profile = profile::upload_skin(&h.ctx, png(64, 64), SkinVariant::Slim).await.unwrap();
+    assert_eq!(profile.skin_url.as_deref(), Some("https://textures.minecraft.net/texture/uploaded"));
+    let upload = &mock.hits(SKINS)[0];
+    assert!(upload.contains("name=\"variant\"\r\n\r\nslim"));
+    assert!(upload.contains("filename=\"skin.png\""));
+    assert!(upload.contains("image/png"));
+
+    // La liste des comptes suit le skin courant.
+    let accounts = kashirlauncher_lib::accounts::AccountIndex::load(&h.ctx.accounts_file).unwrap();
+    assert_eq!(accounts.accounts[0].skin_url, profile.skin_url);
+
+    let profile = profile::set_skin_url(&h.ctx, "https://example.com/skin.png", SkinVariant::Classic)
+        .await
+        .unwrap();
+    assert_eq!(profile.skin_url.as_deref(), Some("https://textures.minecraft.net/texture/linked"));
+    let body: serde_json::Value = serde_json::from_str(&mock.hits(SKINS)[1]).unwrap();
+    assert_eq!(body, json!({ "variant": "classic", "url": "https://example.com/skin.png" }));
+
+    let profile = profile::reset_skin(&h.ctx).await.unwrap();
+    assert_eq!(profile.skin_url.as_deref(), Some("https://textures.minecraft.net/texture/steve"));
+    assert_eq!(mock.hits(ACTIVE_SKIN).len(), 1);
+
+    // Le jeton Minecraft en cache sert à toutes les opérations.
+    assert_eq!(mock.hits(MC_LOGIN).len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_skin_is_rejected_before_any_request() {
+    let mock = Mock::default();
+    mock.happy_path();
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    let err = profile::upload_skin(&h.ctx, png(64, 16), SkinVariant::Classic).await.unwrap_err();
+    assert_eq!(err.code(), "invalid_skin");
+    let err = profile::set_skin_url(&h.ctx, "file:///etc/passwd", SkinVariant::Classic).await.unwrap_err();
+    assert_eq!(err.code(), "invalid_skin");
+    assert!(mock.hits(SKINS).is_empty());
+}
+
+fn profile_with_capes(active: Option<&str>) -> serde_json::Value {
+    let cape = |id: &str, alias: &str| {
+        json!({
+            "id": id,
+            "alias": alias,
+            "state": if active == Some(id) { "ACTIVE" } else { "INACTIVE" },
+            "url": format!("https://textures.minecraft.net/texture/{alias}")
+        })
+    };
+    json!({
+        "id": UUID,
+        "name": "Kashir",
+        "skins": [],
+        "capes": [cape("cape-migrator", "Migrator"), cape("cape-15", "Anniversary")]
+    })
+}
+
+#[tokio::test]
+async fn list_equip_and_hide_capes() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .reset(PROFILE, 200, profile_with_capes(None))
+        .on(ACTIVE_CAPE, 200, profile_with_capes(Some("cape-15")))
+        .on(ACTIVE_CAPE, 200, profile_with_capes(None));
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    let capes = profile::list_capes(&h.ctx).await.unwrap();
+    assert_eq!(capes.len(), 2);
+    assert_eq!(capes[0].alias, "Migrator");
+    assert!(capes.iter().all(|c| c.state == "INACTIVE"));
+
+    let profile = profile::equip_cape(&h.ctx, "cape-15").await.unwrap();
+    let active: Vec<_> = profile.capes.iter().filter(|c| c.state == "ACTIVE").map(|c| c.id.as_str()).collect();
+    assert_eq!(active, ["cape-15"]);
+    let body: serde_json::Value = serde_json::from_str(&mock.hits(ACTIVE_CAPE)[0]).unwrap();
+    assert_eq!(body, json!({ "capeId": "cape-15" }));
+
+    let profile = profile::hide_cape(&h.ctx).await.unwrap();
+    assert!(profile.capes.iter().all(|c| c.state == "INACTIVE"));
+
+    let err = profile::equip_cape(&h.ctx, "cape-vanity").await.unwrap_err();
+    assert_eq!(err, AuthError::UnknownCape("cape-vanity".into()));
+    assert_eq!(mock.hits(ACTIVE_CAPE).len(), 2);
+    assert_eq!(mock.hits(MC_LOGIN).len(), 1);
+
+    // Un 404 des endpoints de skin/cape n'est pas un compte sans profil.
+    mock.reset(ACTIVE_CAPE, 404, json!({ "path": "/minecraft/profile/capes/active", "error": "NOT_FOUND" }));
+    assert_eq!(profile::hide_cape(&h.ctx).await.unwrap_err().code(), "service");
+}
+
+#[test]
+fn profile_name_rules() {
+    for ok in ["abc", "Kashir_42", "A234567890123456"] {
+        profile::validate_profile_name(ok).unwrap();
+    }
+    for bad in ["ab", "A2345678901234567", "with space", "tiret-bas", "é_accent", ""] {
+        assert!(matches!(profile::validate_profile_name(bad), Err(AuthError::InvalidName(_))), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn name_change_eligibility_availability_and_rename() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .on("/mc/minecraft/profile/namechange", 200, json!({
+            "changedAt": "2024-05-01T10:00:00Z",
+            "createdAt": "2015-02-03T00:00:00Z",
+            "nameChangeAllowed": true
+        }))
+        .on("/mc/minecraft/profile/name/Kashir_2/available", 200, json!({ "status": "AVAILABLE" }))
+        .on("/mc/minecraft/profile/name/Notch/available", 200, json!({ "status": "DUPLICATE" }))
+        .on("/mc/minecraft/profile/name/Kashir_2", 200, json!({ "id": UUID, "name": "Kashir_2", "skins": [], "capes": [] }))
+        .on("/mc/minecraft/profile/name/Notch", 403, json!({
+            "path": "/minecraft/profile/name/Notch",
+            "details": { "status": "DUPLICATE" }
+        }));
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    let info = profile::name_change_info(&h.ctx).await.unwrap();
+    assert!(info.allowed);
+    assert_eq!(info.changed_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+
+    assert_eq!(profile::name_available(&h.ctx, "Kashir_2").await.unwrap(), profile::NameAvailability::Available);
+    assert_eq!(profile::name_available(&h.ctx, "Notch").await.unwrap(), profile::NameAvailability::Duplicate);
+
+    let renamed = profile::change_name(&h.ctx, "Kashir_2").await.unwrap();
+    assert_eq!(renamed.name, "Kashir_2");
+    let accounts = kashirlauncher_lib::accounts::AccountIndex::load(&h.ctx.accounts_file).unwrap();
+    assert_eq!(accounts.accounts[0].name, "Kashir_2");
+
+    let err = profile::change_name(&h.ctx, "Notch").await.unwrap_err();
+    assert_eq!(err, AuthError::NameUnavailable("Notch".into()));
+
+    // Refusé localement : aucune requête, même sans jeton en cache.
+    let before = mock.total_hits();
+    assert_eq!(profile::change_name(&h.ctx, "no").await.unwrap_err().code(), "invalid_name");
+    assert_eq!(profile::name_available(&h.ctx, "a/b/../c").await.unwrap_err().code(), "invalid_name");
+    let restarted = AuthContext { state: AuthState::default(), ..h.ctx.clone() };
+    assert_eq!(profile::change_name(&restarted, "Kashir 2").await.unwrap_err().code(), "invalid_name");
+    assert_eq!(mock.total_hits(), before);
+}
+
+#[tokio::test]
+async fn name_change_too_soon() {
+    let mock = Mock::default();
+    mock.happy_path()
+        .on("/mc/minecraft/profile/namechange", 200, json!({ "changedAt": "2026-10-01T00:00:00Z", "nameChangeAllowed": false }))
+        .on("/mc/minecraft/profile/name/Kashir_3", 403, json!({ "path": "/minecraft/profile/name/Kashir_3" }));
+    let h = Harness::new(&mock.serve().await);
+    h.device_login().await.unwrap();
+
+    let info = profile::name_change_info(&h.ctx).await.unwrap();
+    assert!(!info.allowed);
+    assert_eq!(info.created_at, None);
+    assert_eq!(profile::change_name(&h.ctx, "Kashir_3").await.unwrap_err(), AuthError::NameChangeNotAllowed);
+}
@@ -0,0 +1,214 @@
+use kashirlauncher_lib::natives;
+use kashirlauncher_lib::rules::{self, Arch, Features, OsName, Platform};
+use kashirlauncher_lib::version::{Library, Rule, VersionJson};
+use serde_json::json;
+
+fn linux_x64() -> Platform {
+    Platform { os: OsName::Linux, arch: Arch::X86_64, os_version: "6.8.0-45-generic".into() }
+}
+
+fn linux_arm64() -> Platform {
+    Platform { os: OsName::Linux, arch: Arch::Aarch64, os_version: "6.6.31+rpt-rpi-v8".into() }
+}
+
+fn windows(arch: Arch, os_version: &str) -> Platform {
+    Platform { os: OsName::Windows, arch, os_version: os_version.into() }
+}
+
+fn osx() -> Platform {
+    Platform { os: OsName::Osx, arch: Arch::Aarch64, os_version: "14.4".into() }
+}
+
+fn rules(value: serde_json::Value) -> Vec<Rule> {
+    serde_json::from_value(value).unwrap()
+}
+
+fn allowed(value: serde_json::Value, platform: &Platform) -> bool {
+    rules::rules_allow(&rules(value), platform, &Features::default())
+}
+
+/// Noms des librairies retenues pour la plateforme, dans l'ordre du JSON.
+fn applicable(libs: &[Library], platform: &Platform) -> Vec<String> {
+    libs.iter()
+        .filter(|l| rules::library_applies(l, platform))
+        .map(|l| l.name.clone())
+        .collect()
+}
+
+#[test]
+fn no_rules_allows_everything_and_last_match_wins() {
+    assert!(rules::rules_allow(&[], &linux_x64(), &Features::default()));
+    // Aucune règle ne correspond : refusé.
+    assert!(!allowed(json!([{ "action": "allow", "os": { "name": "osx" } }]), &linux_x64()));
+
+    let value = json!([
+        { "action": "allow" },
+        { "action": "disallow", "os": { "name": "linux" } },
+        { "action": "allow", "os": { "name": "linux", "arch": "arm64" } }
+    ]);
+    assert!(allowed(value.clone(), &windows(Arch::X86_64, "10.0.19045")));
+    assert!(!allowed(value.clone(), &linux_x64()));
+    assert!(allowed(value, &linux_arm64()));
+}
+
+#[test]
+fn disallow_on_osx() {
+    let value = json!([
+        { "action": "allow" },
+        { "action": "disallow", "os": { "name": "osx" } }
+    ]);
+    assert!(!allowed(value.clone(), &osx()));
+    assert!(allowed(value.clone(), &linux_x64()));
+    assert!(allowed(value.clone(), &linux_arm64()));
+    assert!(allowed(value, &windows(Arch::X86_64, "10.0.19045")));
+}
+
+#[test]
+fn os_arch_x86_versus_64_bit() {
+    let x86 = json!([{ "action": "allow", "os": { "arch": "x86" } }]);
+    assert!(allowed(x86.clone(), &windows(Arch::X86, "10.0.19045")));
+    assert!(!allowed(x86.clone(), &windows(Arch::X86_64, "10.0.19045")));
+    assert!(!allowed(x86.clone(), &linux_x64()));
+    assert!(!allowed(x86, &linux_arm64()));
+
+    for name in ["x86_64", "amd64"] {
+        let value = json!([{ "action": "allow", "os": { "arch": name } }]);
+        assert!(allowed(value.clone(), &linux_x64()), "{name}");
+        assert!(!allowed(value.clone(), &linux_arm64()), "{name}");
+        assert!(!allowed(value, &windows(Arch::X86, "10.0")), "{name}");
+    }
+    for name in ["arm64", "aarch64"] {
+        let value = json!([{ "action": "allow", "os": { "arch": name } }]);
+        assert!(allowed(value.clone(), &linux_arm64()), "{name}");
+        assert!(!allowed(value, &linux_x64()), "{name}");
+    }
+}
+
+#[test]
+fn os_version_regex() {
+    let win10 = json!([{ "action": "allow", "os": { "name": "windows", "version": "^10\\." } }]);
+    assert!(allowed(win10.clone(), &windows(Arch::X86_64, "10.0.19045")));
+    assert!(!allowed(win10.clone(), &windows(Arch::X86_64, "6.1.7601")));
+    // Le nom de l'OS reste exigé même si la version correspond.
+    assert!(!allowed(win10, &Platform { os_version: "10.0".into(), ..linux_x64() }));
+
+    // Regex invalide : la règle ne correspond jamais.
+    let broken = json!([{ "action": "allow", "os": { "version": "^10\\.(" } }]);
+    assert!(!allowed(broken, &windows(Arch::X86_64, "10.0.19045")));
+}
+
+#[test]
+fn features_gate_rules() {
+    let demo = rules(json!([{ "action": "allow", "features": { "is_demo_user": true } }]));
+    let on = Features { is_demo_user: true, ..Features::default() };
+    assert!(!rules::rules_allow(&demo, &linux_x64(), &Features::default()));
+    assert!(rules::rules_allow(&demo, &linux_x64(), &on));
+
+    // Toutes les features de la règle doivent correspondre, y compris les `false`.
+    let mixed = rules(json!([{
+        "action": "allow",
+        "features": { "has_custom_resolution": true, "is_quick_play_realms": false }
+    }]));
+    let resolution = Features { has_custom_resolution: true, ..Features::default() };
+    let realms = Features { is_quick_play_realms: true, ..resolution.clone() };
+    assert!(rules::rules_allow(&mixed, &linux_x64(), &resolution));
+    assert!(!rules::rules_allow(&mixed, &linux_x64(), &realms));
+
+    // Feature inconnue : considérée désactivée.
+    let unknown = rules(json!([{ "action": "allow", "features": { "is_future_feature": true } }]));
+    assert!(!rules::rules_allow(&unknown, &linux_x64(), &on));
+
+    // Les librairies sont évaluées sans features.
+    let lib: Library = serde_json::from_value(json!({
+        "name": "demo:only:1",
+        "rules": [{ "action": "allow", "features": { "is_demo_user": true } }]
+    }))
+    .unwrap();
+    assert!(!rules::library_applies(&lib, &linux_x64()));
+}
+
+#[test]
+fn library_list_for_each_platform() {
+    let libs: Vec<Library> = serde_json::from_value(json!([
+        { "name": "org.lwjgl:lwjgl:3.3.3" },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.3:natives-linux",
+            "rules": [
+                { "action": "allow", "os": { "name": "linux" } },
+                { "action": "disallow", "os": { "name": "linux", "arch": "arm64" } }
+            ]
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.3:natives-linux-arm64",
+            "rules": [{ "action": "allow", "os": { "name": "linux", "arch": "arm64" } }]
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.3:natives-windows",
+            "rules": [
+                { "action": "allow", "os": { "name": "windows" } },
+                { "action": "disallow", "os": { "name": "windows", "arch": "x86" } }
+            ]
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.3:natives-windows-x86",
+            "rules": [{ "action": "allow", "os": { "name": "windows", "arch": "x86" } }]
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.3:natives-macos",
+            "rules": [{ "action": "allow", "os": { "name": "osx" } }]
+        }
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        applicable(&libs, &linux_x64()),
+        ["org.lwjgl:lwjgl:3.3.3", "org.lwjgl:lwjgl:3.3.3:natives-linux"]
+    );
+    assert_eq!(
+        applicable(&libs, &linux_arm64()),
+        ["org.lwjgl:lwjgl:3.3.3", "org.lwjgl:lwjgl:3.3.3:natives-linux-arm64"]
+    );
+    assert_eq!(
+        applicable(&libs, &windows(Arch::X86_64, "10.0.22631")),
+        ["org.lwjgl:lwjgl:3.3.3", "org.lwjgl:lwjgl:3.3.3:natives-windows"]
+    );
+    assert_eq!(
+        applicable(&libs, &windows(Arch::X86, "10.0.19045")),
+        ["org.lwjgl:lwjgl:3.3.3", "org.lwjgl:lwjgl:3.3.3:natives-windows-x86"]
+    );
+}
+
+#[test]
+fn natives_classifier_per_platform() {
+    let version: VersionJson = serde_json::from_str(include_str!("fixtures/1.7.2.json")).unwrap();
+    let lib = |name: &str| version.libraries.iter().find(|l| l.name == name).unwrap();
+    let lwjgl = lib("org.lwjgl.lwjgl:lwjgl-platform:2.9.0");
+    let twitch = lib("tv.twitch:twitch-platform:5.16");
+    let win64 = windows(Arch::X86_64, "10.0.19045");
+    let win32 = windows(Arch::X86, "10.0.19045");
+
+    assert_eq!(natives::native_classifier(lwjgl, &linux_x64()).as_deref(), Some("natives-linux"));
+    assert_eq!(natives::native_classifier(lwjgl, &linux_arm64()).as_deref(), Some("natives-linux"));
+    assert_eq!(natives::native_classifier(lwjgl, &win64).as_deref(), Some("natives-windows"));
+    assert_eq!(natives::native_classifier(twitch, &win64).as_deref(), Some("natives-windows-64"));
+    assert_eq!(natives::native_classifier(twitch, &win32).as_deref(), Some("natives-windows-32"));
+
+    fn path<'a>(lib: &'a Library, platform: &Platform) -> Option<&'a str> {
+        natives::native_artifact(lib, platform).map(|a| a.path.as_str())
+    }
+    assert_eq!(
+        path(lwjgl, &linux_x64()),
+        Some("org/lwjgl/lwjgl/lwjgl-platform/2.9.0/lwjgl-platform-2.9.0-natives-linux.jar")
+    );
+    assert_eq!(
+        path(twitch, &win32),
+        Some("tv/twitch/twitch-platform/5.16/twitch-platform-5.16-natives-windows-32.jar")
+    );
+    // Classifier déclaré mais absent des téléchargements : rien à extraire.
+    assert_eq!(path(twitch, &linux_x64()), None);
+
+    // Twitch est exclu sous Linux par ses règles, lwjgl-platform sous macOS.
+    assert!(!rules::library_applies(twitch, &linux_x64()));
+    assert!(rules::library_applies(twitch, &win64));
+    assert!(!rules::library_applies(lwjgl, &osx()));
+}
@@ -0,0 +1,84 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use kashirlauncher_lib::download::Downloader;
+use kashirlauncher_lib::install::GameDir;
+use kashirlauncher_lib::jdk::DetectedJdk;
+use kashirlauncher_lib::rules::{Arch, OsName, Platform};
+use kashirlauncher_lib::runtime;
+use kashirlauncher_lib::version::JavaVersion;
+use kashirlauncher_lib::{CancelToken, McError};
+
+#[tokio::test]
+async fn installed_runtime_is_reused_without_network() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    let platform = Platform { os: OsName::Linux, arch: Arch::X86_64, os_version: "6.8.0".into() };
+    let java = JavaVersion { component: "java-runtime-gamma".into(), major_version: 17 };
+
+    // Downloader déjà annulé : toute requête échoue, comme hors ligne.
+    let cancel = CancelToken::default();
+    cancel.cancel();
+    let offline = Downloader::new().unwrap().with_cancel(cancel);
+    let ensure = || runtime::ensure_runtime(&offline, &dirs, &platform, &java, Arc::new(|_, _| {}));
+
+    // Rien d'installé : l'index launchermeta est nécessaire.
+    assert!(matches!(ensure().await.unwrap_err(), McError::Cancelled));
+
+    let runtime_dir = runtime::runtime_dir(&dirs, "java-runtime-gamma");
+    let java_bin = runtime::java_binary(&runtime_dir, &platform);
+    std::fs::create_dir_all(java_bin.parent().unwrap()).unwrap();
+    std::fs::write(&java_bin, b"").unwrap();
+    // Exécutable sans marqueur : installation interrompue, on ne s'y fie pas.
+    assert!(matches!(ensure().await.unwrap_err(), McError::Cancelled));
+
+    std::fs::write(runtime_dir.join(".sha1"), "2ec0cc96c44e5a76b9c8b7c39df7210883d12871").unwrap();
+    assert_eq!(ensure().await.unwrap(), java_bin);
+}
+
+fn jdk(path: &str, major: u32, compatible: bool) -> DetectedJdk {
+    DetectedJdk {
+        path: path.into(),
+        home: String::new(),
+        version: format!("{major}.0.1"),
+        major,
+        arch: "aarch64".into(),
+        vendor: None,
+        managed: false,
+        compatible,
+    }
+}
+
+#[test]
+fn local_java_when_mojang_has_no_runtime() {
+    let platform = Platform { os: OsName::Linux, arch: Arch::Aarch64, os_version: "6.6.31".into() };
+    assert_eq!(runtime::runtime_platform_key(&platform), None);
+    let java = JavaVersion { component: "java-runtime-delta".into(), major_version: 21 };
+
+    let err = runtime::local_java(&[jdk("/usr/lib/jvm/java-17/bin/java", 17, false)], &platform, &java).unwrap_err();
+    assert!(matches!(&err, McError::Runtime(msg) if msg.contains("installez un JDK 21")), "{err}");
+
+    // Liste déjà triée par `jdk::discover` : compatibles d'abord.
+    let jdks = [jdk("/opt/jdk-22/bin/java", 22, true), jdk("/opt/jdk-21/bin/java", 21, true)];
+    assert_eq!(runtime::local_java(&jdks, &platform, &java).unwrap(), Path::new("/opt/jdk-22/bin/java"));
+}
+
+#[tokio::test]
+async fn linux_arm_launch_uses_a_local_jdk_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let dirs = GameDir::new(dir.path().to_path_buf());
+    let platform = Platform { os: OsName::Linux, arch: Arch::Aarch64, os_version: "6.6.31".into() };
+    let java = JavaVersion { component: "java-runtime-gamma".into(), major_version: 17 };
+
+    // JDK arm64 rangé à la main sous `runtime/`, décrit par son fichier `release`.
+    let home = dir.path().join("runtime/temurin-17");
+    std::fs::create_dir_all(home.join("bin")).unwrap();
+    std::fs::write(home.join("bin/java"), b"").unwrap();
+    std::fs::write(home.join("release"), "JAVA_VERSION=\"17.0.10\"\nOS_ARCH=\"aarch64\"\n").unwrap();
+
+    let cancel = CancelToken::default();
+    cancel.cancel();
+    let offline = Downloader::new().unwrap().with_cancel(cancel);
+    let java_bin = runtime::ensure_runtime(&offline, &dirs, &platform, &java, Arc::new(|_, _| {})).await.unwrap();
+    assert!(java_bin.exists(), "{}", java_bin.display());
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::*;
+use kashirlauncher_lib::profile::SkinVariant;
+use kashirlauncher_lib::skin_library::{self, SkinLibrary, SkinView};
+use kashirlauncher_lib::skin_render::{self, SkinTexture};
+use serde_json::json;
+
+const FACE: [u8; 4] = [200, 150, 100, 255];
+const HAT: [u8; 4] = [0, 0, 255, 255];
+const TORSO: [u8; 4] = [0, 200, 0, 255];
+const RIGHT_ARM: [u8; 4] = [255, 0, 0, 255];
+const JACKET: [u8; 4] = [255, 255, 255, 128];
+
+/// (x, y, largeur, hauteur) et couleur.
+type Zone = ((u32, u32, u32, u32), [u8; 4]);
+
+/// Texture RGBA `width`x`height` avec quelques zones colorées.
+fn texture(width: u32, height: u32, zones: &[Zone]) -> Vec<u8> {
+    let mut rgba = vec![0u8; (width * height * 4) as usize];
+    for &((x0, y0, w, h), px) in zones {
+        for y in y0..y0 + h {
+            for x in x0..x0 + w {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&px);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgba).unwrap();
+    writer.finish().unwrap();
+    out
+}
+
+/// Décode un aperçu : (largeur, hauteur, pixels RGBA).
+fn decode(png_data: &[u8]) -> (u32, u32, Vec<u8>) {
+    let mut reader = png::Decoder::new(std::io::Cursor::new(png_data)).read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    (info.width, info.height, buf)
+}
+
+/// Pixel (x, y) en pixels de skin d'un aperçu agrandi `scale` fois.
+fn at(img: &(u32, u32, Vec<u8>), scale: u32, x: u32, y: u32) -> [u8; 4] {
+    let i = (((y * scale) * img.0 + x * scale) * 4) as usize;
+    img.2[i..i + 4].try_into().unwrap()
+}
+
+fn modern_skin() -> Vec<u8> {
+    texture(64, 64, &[
+        ((8, 8, 8, 8), FACE),
+        ((40, 8, 2, 2), HAT),         // coin haut gauche du chapeau
+        ((20, 20, 8, 12), TORSO),
+        ((20, 36, 8, 1), JACKET),     // première ligne de la veste
+        ((44, 20, 4, 12), RIGHT_ARM),
+    ])
+}
+
+#[test]
+fn head_and_body_renders_include_overlays() {
+    let tex = SkinTexture::decode(&modern_skin()).unwrap();
+
+    let head = decode(&skin_render::render_head(&tex, 4).unwrap());
+    assert_eq!((head.0, head.1), (32, 32));
+    assert_eq!(at(&head, 4, 0, 0), HAT);
+    assert_eq!(at(&head, 4, 5, 5), FACE);
+
+    let body = decode(&skin_render::render_body(&tex, SkinVariant::Classic, 2).unwrap());
+    assert_eq!((body.0, body.1), (32, 64));
+    assert_eq!(at(&body, 2, 4, 0), HAT);
+    assert_eq!(at(&body, 2, 8, 4), FACE);
+    assert_eq!(at(&body, 2, 6, 12), TORSO);
+    // Veste à 50 % au-dessus du torse.
+    assert_eq!(at(&body, 2, 6, 8), [128, 227, 128, 255]);
+    // Bras droit du joueur à gauche de l'image ; classique = 4 px de large.
+    assert_eq!(at(&body, 2, 0, 10), RIGHT_ARM);
+
+    let slim = decode(&skin_render::render_body(&tex, SkinVariant::Slim, 1).unwrap());
+    assert_eq!(at(&slim, 1, 0, 10)[3], 0);
+    assert_eq!(at(&slim, 1, 1, 10), RIGHT_ARM);
+}
+
+#[test]
+fn legacy_skin_mirrors_right_limbs() {
+    let legacy = texture(64, 32, &[((8, 8, 8, 8), FACE), ((44, 20, 1, 12), RIGHT_ARM), ((32, 0, 32, 16), [0, 0, 0, 255])]);
+    let tex = SkinTexture::decode(&legacy).unwrap();
+    let body = decode(&skin_render::render_body(&tex, SkinVariant::Classic, 1).unwrap());
+    // Colonne extérieure du bras droit, retrouvée en miroir sur le bras gauche.
+    assert_eq!(at(&body, 1, 0, 10), RIGHT_ARM);
+    assert_eq!(at(&body, 1, 15, 10), RIGHT_ARM);
+    assert_ne!(at(&body, 1, 12, 10), RIGHT_ARM);
+    // Chapeau noir entièrement opaque des anciens skins : ignoré.
+    assert_eq!(at(&body, 1, 8, 4), FACE);
+}
+
+#[test]
+fn import_list_preview_and_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = SkinLibrary::new(dir.path().join("skins"));
+    assert!(library.list().unwrap().is_empty());
+
+    let first = library.import(&modern_skin(), "Halloween", SkinVariant::Slim, None).unwrap();
+    let second = library.import(&modern_skin(), "  ", SkinVariant::Classic, None).unwrap();
+    assert_eq!(second.name, "Skin");
+    assert_eq!(library.list().unwrap().len(), 2);
+    assert_eq!(library.texture(&first.id).unwrap(), modern_skin());
+
+    let head = decode(&library.preview(&first.id, SkinView::Head).unwrap());
+    assert_eq!((head.0, head.1), (64, 64));
+    // Aperçu supprimé : régénéré depuis la texture.
+    std::fs::remove_file(dir.path().join("skins").join(format!("{}.body.png", first.id))).unwrap();
+    let body = decode(&library.preview(&first.id, SkinView::Body).unwrap());
+    assert_eq!((body.0, body.1), (128, 256));
+
+    assert!(library.import(&texture(32, 32, &[]), "x", SkinVariant::Classic, None).is_err());
+    assert_eq!(library.get("../accounts").unwrap_err().code(), "skin_library");
+
+    library.remove(&first.id).unwrap();
+    assert!(!dir.path().join("skins").join(format!("{}.png", first.id)).exists());
+    let left: Vec<_> = library.list().unwrap().into_iter().map(|s| s.id).collect();
+    assert_eq!(left, [second.id]);
+}
+
+#[tokio::test]
+async fn import_current_profile_skin() {
+    let mock = Mock::default();
+    let base = mock.serve().await;
+    mock.happy_path()
+        .reset(PROFILE, 200, json!({
+            "id": UUID,
+            "name": "Kashir",
+            "skins": [{ "id": "s1", "state": "ACTIVE", "url": format!("{base}/texture/abc"), "variant": "SLIM" }],
+            "capes": []
+        }))
+        .on_bytes("/texture/abc", 200, modern_skin());
+    let h = Harness::new(&base);
+    h.device_login().await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let library = SkinLibrary::new(dir.path().to_path_buf());
+    let entry = skin_library::import_current(&library, &h.ctx, None).await.unwrap();
+    assert_eq!(entry.name, "Kashir");
+    assert_eq!(entry.variant, SkinVariant::Slim);
+    assert_eq!(entry.source_url, Some(format!("{base}/texture/abc")));
+    assert_eq!(library.texture(&entry.id).unwrap(), modern_skin());
+}
@@ -1,7 +1,9 @@
 import { useEffect, useMemo, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useNavigate } from "react-router-dom";
 import { open as openExternal } from "@tauri-apps/plugin-shell";
+import { authErrorCode, authErrorMessage, isAuthError } from "./authError";
 
 type DeviceStart = {
     user_code: string;
@@ -10,9 +12,16 @@ type DeviceStart = {
     expires_in: number;   // sec
     interval: number;     // sec
     message: string;
+    poll_id: string;
 };
 
-type Phase = "starting" | "waiting" | "linking" | "done" | "error";
+type PollStatus = {
+    poll_id: string;
+    status: "pending" | "slow_down" | "expired" | "denied" | "cancelled" | "success";
+    interval?: number;  // sec, pour slow_down
+};
+
+type Phase = "starting" | "waiting" | "linking" | "profile" | "done" | "error";
 type ErrorKind = "expired" | "generic";
 
 export default function AuthLoading() {
@@ -23,10 +32,16 @@ export default function AuthLoading() {
     const [error, setError] = useState<string | null>(null);
     const [errorKind, setErrorKind] = useState<ErrorKind>("generic");
     const [copied, setCopied] = useState(false);
+    const [note, setNote] = useState<string | null>(null);
+    const [profileName, setProfileName] = useState("");
+    const [profileError, setProfileError] = useState<string | null>(null);
+    const [creating, setCreating] = useState(false);
+    const pollIdRef = useRef<string | null>(null);
     const initial = useRef<number>(0);
     const [secondsLeft, setSecondsLeft] = useState<number>(0);
     const openedSystemRef = useRef<boolean>(false);
     const cancelledRef = useRef<boolean>(false);
+    const profileSetupRef = useRef<boolean>(false);
     const timerRef = useRef<number | null>(null);
 
     const mmss = (s: number) => {
@@ -58,6 +73,19 @@ export default function AuthLoading() {
         }
     };
 
+    const cancelPoll = () => {
+        const pollId = pollIdRef.current;
+        pollIdRef.current = null;
+        if (pollId) invoke("mc_ms_cancel_poll", { pollId }).catch(() => {});
+    };
+
+    // Création de profil abandonnée : la connexion en attente est oubliée côté Rust.
+    const cancelProfileSetup = () => {
+        if (!profileSetupRef.current) return;
+        profileSetupRef.current = false;
+        invoke("mc_ms_cancel_profile_setup").catch(() => {});
+    };
+
     const startSequence = async () => {
         // reset état
         cancelledRef.current = false;
@@ -65,13 +93,18 @@ export default function AuthLoading() {
         setError(null);
         setErrorKind("generic");
         setInfo(null);
+        setNote(null);
         initial.current = 0;
         setSecondsLeft(0);
 
         try {
             // Étape 1 : device code
             const start = await invoke<DeviceStart>("mc_ms_start_device_code");
-            if (cancelledRef.current) return;
+            pollIdRef.current = start.poll_id;
+            if (cancelledRef.current) {
+                cancelPoll();
+                return;
+            }
 
             setInfo(start);
             initial.current = start.expires_in;
@@ -90,11 +123,13 @@ export default function AuthLoading() {
             // Attend la validation (stocke le refresh token côté Rust)
             await invoke("mc_ms_poll_and_store", {
                 args: {
+                    poll_id: start.poll_id,
                     device_code: start.device_code,
                     interval_secs: start.interval,
                     timeout_secs: Math.min(start.expires_in, 600)
                 }
             });
+            pollIdRef.current = null;
             if (cancelledRef.current) return;
 
             // Étape 2 : chaîne → profil
@@ -107,18 +142,45 @@ export default function AuthLoading() {
             nav("/minecraft", { replace: true });
         } catch (e: any) {
             if (cancelledRef.current) return;
-            const msg = String(e || "");
-            setError(msg);
-            setErrorKind(msg.toLowerCase().includes("expired") ? "expired" : "generic");
+            if (authErrorCode(e) === "cancelled") return;
+            stopTimer();
+            // Jeu acheté sans profil Java : la connexion reste en attente côté Rust.
+            if (authErrorCode(e) === "no_profile") {
+                profileSetupRef.current = true;
+                setProfileError(null);
+                setPhase("profile");
+                return;
+            }
+            setError(authErrorMessage(e));
+            if (isAuthError(e) && e.detail) console.error("[auth]", e.code, e.detail);
+            const code = authErrorCode(e);
+            setErrorKind(code === "device_code_expired" || code === "timeout" ? "expired" : "generic");
             setPhase("error");
             stopTimer();
         }
     };
 
+    useEffect(() => {
+        const unlisten = listen<PollStatus>("auth://status", (e) => {
+            const s = e.payload;
+            if (s.poll_id !== pollIdRef.current) return;
+            if (s.status === "slow_down" && s.interval) {
+                setNote(`Microsoft demande de ralentir : vérification toutes les ${s.interval} s.`);
+            } else if (s.status === "success") {
+                setNote(null);
+                setPhase("linking");
+                stopTimer();
+            }
+        });
+        return () => { unlisten.then((f) => f()); };
+    }, []);
+
     useEffect(() => {
         startSequence();
         return () => {
             cancelledRef.current = true;
+            cancelPoll();
+            cancelProfileSetup();
             stopTimer();
         };
         // eslint-disable-next-line react-hooks/exhaustive-deps
@@ -133,8 +195,32 @@ export default function AuthLoading() {
         } catch {}
     };
 
+    const createProfile = async () => {
+        const name = profileName.trim();
+        if (!/^[A-Za-z0-9_]{3,16}$/.test(name)) {
+            setProfileError("3 à 16 caractères : lettres, chiffres et _ uniquement.");
+            return;
+        }
+        setCreating(true);
+        setProfileError(null);
+        try {
+            await invoke("mc_ms_create_profile", { name });
+            profileSetupRef.current = false;
+            if (cancelledRef.current) return;
+            setPhase("done");
+            nav("/minecraft", { replace: true });
+        } catch (e) {
+            setProfileError(authErrorMessage(e));
+            if (isAuthError(e) && e.detail) console.error("[auth]", e.code, e.detail);
+        } finally {
+            setCreating(false);
+        }
+    };
+
     const handleBack = () => {
         cancelledRef.current = true; // on ignore les résolutions tardives
+        cancelPoll();
+        cancelProfileSetup();
         stopTimer();
         nav(-1); // ou nav("/minecraft")
     };
@@ -143,7 +229,7 @@ export default function AuthLoading() {
 
     // Badge selon la phase
     const stepPill =
-        phase === "linking" || phase === "done"
+        phase === "linking" || phase === "profile" || phase === "done"
             ? <span className="pill live">Étape 2/2</span>
             : <span className="pill alpha">Étape 1/2</span>;
 
@@ -162,6 +248,7 @@ export default function AuthLoading() {
                         {phase === "waiting" &&
                             `Valide la connexion dans ${openedSystemRef.current ? "le navigateur" : "la fenêtre"}…`}
                         {phase === "linking" && "Finalisation de la connexion et récupération du profil…"}
+                        {phase === "profile" && "Dernière étape : choisis ton pseudo Minecraft."}
                         {phase === "done" && "Connexion réussie."}
           </span>
                 </div>
@@ -200,9 +287,37 @@ export default function AuthLoading() {
                         {phase === "waiting" && <span>En attente de validation…</span>}
                         {phase === "linking" && <span>Connexion à Minecraft…</span>}
                         {info && phase === "waiting" && <span className="muted">Expire dans {mmss(secondsLeft)}</span>}
+                        {note && phase === "waiting" && <span className="muted">{note}</span>}
                     </div>
                 </div>
 
+                {/* Compte sans profil Java : création avec le pseudo choisi */}
+                {phase === "profile" && (
+                    <div className="auth-profile">
+                        <p className="muted">
+                            Ce compte possède Minecraft mais n'a pas encore de profil Java.
+                            Choisis ton pseudo (modifiable ensuite une fois tous les 30 jours).
+                        </p>
+                        <div className="auth-code-row">
+                            <input
+                                value={profileName}
+                                onChange={(e) => setProfileName(e.target.value)}
+                                onKeyDown={(e) => { if (e.key === "Enter" && !creating) createProfile(); }}
+                                maxLength={16}
+                                placeholder="Pseudo"
+                                aria-label="Pseudo Minecraft"
+                                disabled={creating}
+                            />
+                            <div className="auth-actions">
+                                <button className="primary" onClick={createProfile} disabled={creating}>
+                                    {creating ? "Création…" : "Créer le profil"}
+                                </button>
+                            </div>
+                        </div>
+                        {profileError && <div className="muted" role="alert" style={{ marginTop: 6 }}>{profileError}</div>}
+                    </div>
+                )}
+
                 {/* Erreurs */}
                 {phase === "error" && (
                     <div className="auth-error" role="alert">
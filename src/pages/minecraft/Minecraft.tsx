@@ -5,13 +5,27 @@ import { listen, UnlistenFn } from "@tauri-apps/api/event";
 /* ===== Types d'events côté Rust ===== */
 type ProgressPayload = { step: string; percent: number; detail?: string | null };
 type LogPayload = { line: string };
-type DonePayload = { ok: boolean; error?: string | null };
+type DonePayload = {
+    launch_id: string;
+    ok: boolean;
+    cancelled: boolean;
+    error?: string | null;
+    exit_code?: number | null;
+};
 
 /* ===== Profil (light) ===== */
 type McProfileLite = {
     id: string;
     name: string;
     skin_url?: string | null;
+    skin_variant?: "classic" | "slim" | null;
+    capes?: McCape[];
+};
+type McCape = {
+    id: string;
+    alias: string;
+    state: "ACTIVE" | "INACTIVE";
+    url: string;
 };
 
 /* ===== Données serveurs (dummy) ===== */
@@ -114,6 +128,7 @@ export default function Minecraft() {
 
     // Lancement & progression
     const [launching, setLaunching] = useState(false);
+    const [launchId, setLaunchId] = useState<string | null>(null);
     const [progress, setProgress] = useState(0);
     const [stage, setStage] = useState<string>("idle");
     const [logs, setLogs] = useState<string[]>([]);
@@ -177,13 +192,23 @@ export default function Minecraft() {
                 unsubs.push(
                     await listen<DonePayload>("mc://done", (e) => {
                         setLaunching(false);
+                        setLaunchId(null);
                         setProgress(100);
-                        if (!e.payload.ok) {
+                        if (e.payload.cancelled) {
+                            setStage("arrêté");
+                        } else if (!e.payload.ok) {
                             setLogs((cur) => [...cur, `\n[ERREUR] ${e.payload.error ?? "inconnue"}`]);
                             setTab("logs");
                         }
                     })
                 );
+                unsubs.push(
+                    await listen("auth://relogin", () => {
+                        setConnected(false);
+                        setProfile({ id: "0000-0000-FAKE", name: "Player42", skin_url: null });
+                        setLogs((cur) => [...cur, "[AUTH] Session expirée, reconnexion nécessaire."]);
+                    })
+                );
             } catch (err) {
                 // Si permissions manquantes, on ne crashe pas l’UI
                 console.error("[mc] listen events failed:", err);
@@ -206,7 +231,8 @@ export default function Minecraft() {
         setLogs([]);
         try {
             const ramMb = Math.round((ramGo || server.defaultRamGo) * 1024);
-            await invoke("mc_launch_server", { args: { server_id: selected, ram_mb: ramMb } });
+            const id = await invoke<string>("mc_launch_server", { args: { server_id: selected, ram_mb: ramMb } });
+            setLaunchId(id);
             // la suite se fait via les events
         } catch (e: any) {
             setLaunching(false);
@@ -216,6 +242,15 @@ export default function Minecraft() {
         }
     };
 
+    const onStop = async () => {
+        if (!launchId) return;
+        try {
+            await invoke("mc_kill_game", { launchId });
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
     const onLogout = async () => {
         try {
             await invoke("auth_logout");
@@ -359,9 +394,15 @@ export default function Minecraft() {
                                                 <div className="muted">Serveur: <strong>{server.version}</strong></div>
                                                 <div className="muted">Étape: <strong>{stage}</strong></div>
                                             </div>
-                                            <button className="play-giant" onClick={onPlay} disabled={launching}>
-                                                {launching ? "En cours…" : "Jouer"}
-                                            </button>
+                                            {launching && launchId ? (
+                                                <button className="play-giant" onClick={onStop}>
+                                                    Arrêter
+                                                </button>
+                                            ) : (
+                                                <button className="play-giant" onClick={onPlay} disabled={launching}>
+                                                    {launching ? "En cours…" : "Jouer"}
+                                                </button>
+                                            )}
                                         </div>
 
                                         {/* Progress */}
@@ -0,0 +1,19 @@
+// Erreur renvoyée par les commandes d'authentification (AuthError côté Rust).
+export type AuthError = {
+    code: string;
+    message: { fr: string; en: string };
+    detail?: string | null;
+};
+
+export function isAuthError(e: unknown): e is AuthError {
+    return typeof e === "object" && e !== null && "code" in e && "message" in e;
+}
+
+export function authErrorCode(e: unknown): string | null {
+    return isAuthError(e) ? e.code : null;
+}
+
+export function authErrorMessage(e: unknown, lang: "fr" | "en" = "fr"): string {
+    if (isAuthError(e)) return e.message[lang];
+    return String(e || "");
+}